@@ -1,4 +1,6 @@
-use syn::{Attribute, Expr};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Expr, Token};
 
 /// Represents different types of argument attributes
 #[derive(Clone)]
@@ -6,7 +8,98 @@ pub enum ArgAttribute {
     /// #\[show\] - Include in debug output with default formatting
     Show,
     /// #\[show(expr)\] - Include in debug output with custom formatter
-    ShowWithExpression { formatter: Expr },
+    ShowWithExpression { formatter: Box<Expr> },
+    /// #\[show(preset)\] - Include in debug output with a built-in formatter
+    ShowWithPreset { preset: FormatPreset },
+    /// #\[show(after)\], #\[show(both)\], #\[show(diff)\] - Choose when to include in debug output
+    ShowAt { timing: ShowTiming },
+}
+
+/// When an argument is included in debug output
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShowTiming {
+    /// Default - In the call header only
+    #[default]
+    Entry,
+    /// `after` - On the return line only
+    Exit,
+    /// `both` - In the call header and on the return line
+    Both,
+    /// `diff` - In the call header, and on the return line as `before → after` if changed
+    Diff,
+}
+
+impl Parse for ShowTiming {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ident: syn::Ident = input.parse()?;
+
+        match ident.to_string().as_str() {
+            "after" => Ok(ShowTiming::Exit),
+            "both" => Ok(ShowTiming::Both),
+            "diff" => Ok(ShowTiming::Diff),
+            _ => Err(syn::Error::new(ident.span(), "unknown timing")),
+        }
+    }
+}
+
+/// Built-in formatters available as `#[show(preset)]`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FormatPreset {
+    /// `hex` - `{:#x}`
+    Hex,
+    /// `bin` or `bin = N` - `{:b}`, zero-padded to N digits if given
+    Bin { width: Option<usize> },
+    /// `len` - Length of a collection
+    Len,
+    /// `display` - `{}`
+    Display,
+    /// `pretty` - `{:#?}`
+    Pretty,
+    /// `bits` - Indices of the set bits of an integer, e.g. `{0, 2, 5}`
+    Bits,
+}
+
+impl Parse for FormatPreset {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ident: syn::Ident = input.parse()?;
+
+        let preset = match ident.to_string().as_str() {
+            "hex" => FormatPreset::Hex,
+            "bin" => {
+                let width = if input.peek(syn::Token![=]) {
+                    input.parse::<syn::Token![=]>()?;
+                    let width: syn::LitInt = input.parse()?;
+                    Some(width.base10_parse::<usize>()?)
+                } else {
+                    None
+                };
+                FormatPreset::Bin { width }
+            }
+            "len" => FormatPreset::Len,
+            "display" => FormatPreset::Display,
+            "pretty" => FormatPreset::Pretty,
+            "bits" => FormatPreset::Bits,
+            _ => return Err(syn::Error::new(ident.span(), "unknown preset")),
+        };
+
+        Ok(preset)
+    }
+}
+
+/// A timing or a preset in a list like `#[show(len, diff)]`
+enum ShowOption {
+    Timing(ShowTiming),
+    Preset(FormatPreset),
+}
+
+impl Parse for ShowOption {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.fork().parse::<ShowTiming>().is_ok() {
+            input.parse().map(ShowOption::Timing)
+        } else {
+            input.parse().map(ShowOption::Preset)
+        }
+    }
 }
 
 /// Parsed argument attributes
@@ -21,8 +114,21 @@ impl ArgAttributes {
 
         for attr in attrs {
             if attr.path().is_ident("show") {
-                if let Ok(formatter) = attr.parse_args::<Expr>() {
-                    parsed_attrs.push(ArgAttribute::ShowWithExpression { formatter });
+                // Timings and presets take precedence over expressions which are a bare name
+                let options =
+                    attr.parse_args_with(Punctuated::<ShowOption, Token![,]>::parse_terminated);
+                if let Ok(options) = options.as_ref().map(Punctuated::iter) {
+                    if options.len() == 0 {
+                        parsed_attrs.push(ArgAttribute::Show);
+                    }
+                    parsed_attrs.extend(options.map(|option| match *option {
+                        ShowOption::Timing(timing) => ArgAttribute::ShowAt { timing },
+                        ShowOption::Preset(preset) => ArgAttribute::ShowWithPreset { preset },
+                    }));
+                } else if let Ok(formatter) = attr.parse_args::<Expr>() {
+                    parsed_attrs.push(ArgAttribute::ShowWithExpression {
+                        formatter: Box::new(formatter),
+                    });
                 } else {
                     parsed_attrs.push(ArgAttribute::Show);
                 }
@@ -43,12 +149,47 @@ impl ArgAttributes {
     pub fn get_custom_formatter(&self) -> Option<&Expr> {
         self.attrs.iter().find_map(|attr| {
             if let ArgAttribute::ShowWithExpression { formatter } = attr {
-                Some(formatter)
+                Some(&**formatter)
             } else {
                 None
             }
         })
     }
+
+    /// Get formatter preset (first one found)
+    pub fn get_preset(&self) -> Option<FormatPreset> {
+        self.attrs.iter().find_map(|attr| {
+            if let ArgAttribute::ShowWithPreset { preset } = attr {
+                Some(*preset)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Get when this argument is shown (first one found)
+    pub fn get_timing(&self) -> ShowTiming {
+        self.attrs
+            .iter()
+            .find_map(|attr| {
+                if let ArgAttribute::ShowAt { timing } = attr {
+                    Some(*timing)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or_default()
+    }
+
+    /// Check if this argument should be printed in the call header
+    pub fn should_print_on_entry(&self) -> bool {
+        self.should_print() && self.get_timing() != ShowTiming::Exit
+    }
+
+    /// Check if this argument should be printed on the return line
+    pub fn should_print_on_exit(&self) -> bool {
+        self.should_print() && self.get_timing() != ShowTiming::Entry
+    }
 }
 
 #[cfg(test)]
@@ -72,12 +213,33 @@ mod tests {
         assert!(attrs.get_custom_formatter().is_some());
     }
 
+    #[test]
+    fn test_show_with_preset_and_timing() {
+        let attr: Attribute = parse_quote!(#[show(bin = 4, diff)]);
+        let attrs = ArgAttributes::from_attrs(&[attr]);
+        assert_eq!(
+            attrs.get_preset(),
+            Some(FormatPreset::Bin { width: Some(4) })
+        );
+        assert_eq!(attrs.get_timing(), ShowTiming::Diff);
+        assert!(attrs.get_custom_formatter().is_none());
+    }
+
+    #[test]
+    fn test_show_with_parenthesized_name() {
+        let attr: Attribute = parse_quote!(#[show((len))]);
+        let attrs = ArgAttributes::from_attrs(&[attr]);
+        assert!(attrs.get_preset().is_none());
+        assert!(attrs.get_custom_formatter().is_some());
+    }
+
     #[test]
     fn test_basic_show() {
         let attr: Attribute = parse_quote!(#[show]);
         let attrs = ArgAttributes::from_attrs(&[attr]);
         assert!(attrs.should_print());
         assert!(attrs.get_custom_formatter().is_none());
+        assert!(attrs.get_preset().is_none());
     }
 
     #[test]
@@ -85,4 +247,50 @@ mod tests {
         let attrs = ArgAttributes::from_attrs(&[]);
         assert!(!attrs.should_print());
     }
+
+    #[test]
+    fn test_show_with_preset() {
+        let attr: Attribute = parse_quote!(#[show(hex)]);
+        let attrs = ArgAttributes::from_attrs(&[attr]);
+        assert!(attrs.should_print());
+        assert_eq!(attrs.get_preset(), Some(FormatPreset::Hex));
+        assert!(attrs.get_custom_formatter().is_none());
+    }
+
+    #[test]
+    fn test_show_with_preset_width() {
+        let attr: Attribute = parse_quote!(#[show(bin = 8)]);
+        let attrs = ArgAttributes::from_attrs(&[attr]);
+        assert_eq!(
+            attrs.get_preset(),
+            Some(FormatPreset::Bin { width: Some(8) })
+        );
+    }
+
+    #[test]
+    fn test_unknown_preset_is_expression() {
+        let attr: Attribute = parse_quote!(#[show(hexadecimal)]);
+        let attrs = ArgAttributes::from_attrs(&[attr]);
+        assert!(attrs.get_preset().is_none());
+        assert!(attrs.get_custom_formatter().is_some());
+    }
+
+    #[test]
+    fn test_show_timing() {
+        let attrs = ArgAttributes::from_attrs(&[parse_quote!(#[show])]);
+        assert!(attrs.should_print_on_entry());
+        assert!(!attrs.should_print_on_exit());
+
+        let attrs = ArgAttributes::from_attrs(&[parse_quote!(#[show(after)])]);
+        assert_eq!(attrs.get_timing(), ShowTiming::Exit);
+        assert!(!attrs.should_print_on_entry());
+        assert!(attrs.should_print_on_exit());
+
+        let attrs =
+            ArgAttributes::from_attrs(&[parse_quote!(#[show(len)]), parse_quote!(#[show(diff)])]);
+        assert_eq!(attrs.get_timing(), ShowTiming::Diff);
+        assert_eq!(attrs.get_preset(), Some(FormatPreset::Len));
+        assert!(attrs.should_print_on_entry());
+        assert!(attrs.should_print_on_exit());
+    }
 }
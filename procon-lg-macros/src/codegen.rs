@@ -2,7 +2,22 @@ use quote::quote;
 use syn::visit_mut::VisitMut;
 use syn::{Attribute, FnArg, Pat, PatIdent};
 
-use crate::{arg_attrs::ArgAttributes, macro_args::MacroArgs, visitor::Visitor};
+use crate::{
+    arg_attrs::{ArgAttributes, FormatPreset, ShowTiming},
+    macro_args::{LimitAction, MacroArgs, ReturnFormatter},
+    visitor::Visitor,
+};
+
+/// Point where the instrumented function is left
+#[derive(Clone, Copy)]
+enum Exit {
+    /// End of the function body, with the result in `ans`
+    End,
+    /// `return expr`, with the result in `__lg_return_val`
+    Return,
+    /// `return` without a value
+    ReturnUnit,
+}
 
 /// Code generator
 pub struct CodeGenerator {
@@ -11,92 +26,343 @@ pub struct CodeGenerator {
 }
 
 impl CodeGenerator {
-    /// Generate helper macros for multiline print support
-    fn generate_helper_macros() -> proc_macro2::TokenStream {
+    /// Generate creation of the depth guard
+    fn generate_depth_guard(&self, fn_name: &syn::Ident) -> proc_macro2::TokenStream {
+        let mut options = vec![quote! { name: stringify!(#fn_name) }];
+        if let Some(when) = &self.macro_args.when {
+            options.push(quote! { visible: #when });
+        }
+        if let Some(focus) = &self.macro_args.focus {
+            options.push(quote! { focus: Some(#focus) });
+        }
+        if let Some(max_depth) = self.macro_args.max_depth {
+            options.push(quote! { max_depth: Some(#max_depth) });
+        }
+        if let Some((first, last)) = self.macro_args.children_limit {
+            options.push(quote! { children_limit: Some((#first, #last)) });
+        }
+        if self.macro_args.report_overlap {
+            options.push(quote! { report_overlap: true });
+        }
+        if self.macro_args.stats {
+            options.push(quote! { stats: true });
+        }
+        if self.macro_args.time {
+            options.push(quote! { time: true });
+        }
+        if self.macro_args.compact {
+            options.push(quote! { compact: true });
+        }
+        if let Some(style) = &self.macro_args.style {
+            options.push(quote! { style: Some(procon_lg::TreeStyle::#style) });
+        }
+        if let Some(max_guides) = self.macro_args.max_guides {
+            options.push(quote! { max_guides: Some(#max_guides) });
+        }
+
         quote! {
-            macro_rules! __lg_print {
-                ($print_macro:ident, $level:expr, $($args:tt)*) => {
-                    {
-                        let __lg_formatted = format!($($args)*);
-                        for __lg_line in __lg_formatted.lines() {
-                            $print_macro!("{}{}", "│ ".repeat($level), __lg_line);
-                        }
-                        if __lg_formatted.ends_with('\n') {
-                            $print_macro!("{}", "│ ".repeat($level));
+            let __procon_lg_depth_guard = DepthGuard::with_options(procon_lg::CallOptions {
+                #(#options,)*
+                ..procon_lg::CallOptions::default()
+            });
+        }
+    }
+
+    /// Generate the exceeded conditions of the configured limits, with their messages
+    fn generate_limit_conditions(
+        &self,
+        fn_name: &syn::Ident,
+    ) -> Vec<(proc_macro2::TokenStream, proc_macro2::TokenStream)> {
+        let mut conditions = Vec::new();
+        if let Some(limit) = self.macro_args.recursion_limit {
+            conditions.push((
+                quote! { __procon_lg_depth_guard.current_depth() >= #limit },
+                quote! {
+                    format!(
+                        "Recursion limit exceeded: {} reached maximum depth of {}",
+                        stringify!(#fn_name),
+                        #limit
+                    )
+                },
+            ));
+        }
+        if let Some(limit) = self.macro_args.call_limit {
+            conditions.push((
+                quote! { __procon_lg_depth_guard.call_count() > #limit },
+                quote! {
+                    format!(
+                        "Call limit exceeded: {} reached maximum call count of {}",
+                        stringify!(#fn_name),
+                        #limit
+                    )
+                },
+            ));
+        }
+        if let Some(limit) = self.macro_args.time_limit_ms {
+            conditions.push((
+                quote! {
+                    __procon_lg_depth_guard.elapsed() > std::time::Duration::from_millis(#limit)
+                },
+                quote! {
+                    format!(
+                        "Time limit exceeded: {} reached maximum time of {} ms",
+                        stringify!(#fn_name),
+                        #limit
+                    )
+                },
+            ));
+        }
+        conditions
+    }
+
+    /// Generate limit checks run before the call header is printed
+    ///
+    /// The recursion limit panics here, while `truncate` hides the call and `warn` prints its
+    /// warning before any output of the call.
+    fn generate_recursion_check(&self, fn_name: &syn::Ident) -> proc_macro2::TokenStream {
+        let conditions = self.generate_limit_conditions(fn_name);
+        let checks = conditions
+            .iter()
+            .enumerate()
+            .map(|(i, (condition, message))| {
+                let action = match &self.macro_args.on_limit {
+                    // The recursion limit comes first, and panics before the header is printed
+                    LimitAction::Panic if i == 0 && self.macro_args.recursion_limit.is_some() => {
+                        quote! {
+                            procon_lg::flush_output();
+                            panic!("{}", #message);
                         }
                     }
+                    LimitAction::Truncate => quote! {
+                        __procon_lg_depth_guard.hide();
+                    },
+                    LimitAction::Warn => quote! {
+                        static __LG_WARNED: std::sync::atomic::AtomicBool =
+                            std::sync::atomic::AtomicBool::new(false);
+                        if !__LG_WARNED.swap(true, std::sync::atomic::Ordering::Relaxed) {
+                            procon_lg::print_warning(&#message);
+                        }
+                    },
+                    LimitAction::Panic | LimitAction::Fallback(_) => return quote! {},
                 };
-            }
+                quote! {
+                    if #condition {
+                        #action
+                    }
+                }
+            });
+        quote! { #(#checks)* }
+    }
 
-            macro_rules! __lg_print_no_newline {
-                ($print_macro:ident, $level:expr, $($args:tt)*) => {
-                    {
-                        let __lg_formatted = format!($($args)*);
-                        let __lg_lines: Vec<&str> = __lg_formatted.lines().collect();
-                        for (i, __lg_line) in __lg_lines.iter().enumerate() {
-                            if i == 0 {
-                                $print_macro!("{}{}", "│ ".repeat($level), __lg_line);
-                            } else {
-                                $print_macro!("\n{}{}", "│ ".repeat($level), __lg_line);
-                            }
-                        }
-                        if __lg_formatted.ends_with('\n') {
-                            $print_macro!("\n{}", "│ ".repeat($level));
+    /// Generate limit checks run after the call header is printed
+    ///
+    /// Call count and time limits panic with the call stack, including the current call.
+    /// A fallback value is returned with a return line.
+    fn generate_limit_checks(&self, fn_name: &syn::Ident) -> proc_macro2::TokenStream {
+        let mut conditions = self.generate_limit_conditions(fn_name);
+        match &self.macro_args.on_limit {
+            LimitAction::Panic => {
+                if self.macro_args.recursion_limit.is_some() {
+                    conditions.remove(0);
+                }
+                let checks = conditions.iter().map(|(condition, message)| {
+                    quote! {
+                        if #condition {
+                            panic!("{}\n{}", #message, procon_lg::call_stack());
                         }
                     }
-                };
+                });
+                quote! { #(#checks)* }
+            }
+            LimitAction::Fallback(fallback) if !conditions.is_empty() => {
+                let conditions = conditions.iter().map(|(condition, _)| condition);
+                let exit_output = self.generate_exit_output(Exit::Return);
+                quote! {
+                    if #(#conditions)||* {
+                        let __lg_return_val = #fallback;
+                        #exit_output
+                        return __lg_return_val;
+                    }
+                }
             }
+            LimitAction::Truncate | LimitAction::Warn | LimitAction::Fallback(_) => quote! {},
         }
     }
 
-    /// Generate recursion depth check
-    fn generate_recursion_check(&self, fn_name: &syn::Ident) -> proc_macro2::TokenStream {
-        if let Some(limit) = self.macro_args.recursion_limit {
-            quote! {
-                if __procon_lg_depth_guard.current_depth() >= #limit {
-                    panic!("Recursion limit exceeded: {} reached maximum depth of {}", stringify!(#fn_name), #limit);
-                }
+    /// Generate output for leaving the function
+    fn generate_exit_output(&self, exit: Exit) -> proc_macro2::TokenStream {
+        let print = self.generate_exit_print(exit);
+        let mark_early_return = match exit {
+            Exit::End => quote! {},
+            Exit::Return | Exit::ReturnUnit => quote! {
+                __procon_lg_depth_guard.mark_early_return();
+            },
+        };
+        quote! {
+            #mark_early_return
+            if __procon_lg_depth_guard.is_visible() {
+                #print
             }
-        } else {
-            quote! {}
         }
     }
 
-    /// Generate return value output
-    fn generate_return_output(&self) -> proc_macro2::TokenStream {
-        if self.macro_args.show_return {
-            quote! {
-                eprintln!(
-                    "{}└ return: {:?}",
-                    "│ ".repeat(__procon_lg_depth_guard.current_depth()),
-                    ans
-                );
+    /// Generate the return line printed when leaving the function
+    ///
+    /// The runtime draws the glyph of the line, e.g. `└ return: 3`, or `╵` if there is no text.
+    fn generate_exit_print(&self, exit: Exit) -> proc_macro2::TokenStream {
+        let return_value = match exit {
+            Exit::End if self.macro_args.show_return => {
+                Some(self.generate_return_value(&quote! { ans }))
             }
-        } else {
-            quote! {
-                eprintln!(
-                    "{}╵",
-                    "│ ".repeat(__procon_lg_depth_guard.current_depth()),
-                );
+            Exit::Return => Some(self.generate_return_value(&quote! { __lg_return_val })),
+            Exit::End | Exit::ReturnUnit => None,
+        }
+        .map(|value| quote! { procon_lg::Painted(procon_lg::Role::Value, #value) });
+        let exit_arg_exprs = self.generate_exit_arg_format_expressions();
+        let suffix = concat_displays(
+            [
+                self.generate_watch_suffix(),
+                self.generate_time_suffix(),
+                self.generate_call_id_suffix(),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+        );
+
+        if exit_arg_exprs.is_empty() && suffix.is_none() {
+            return match (exit, return_value) {
+                (_, Some(return_value)) => quote! {
+                    __procon_lg_depth_guard.print_exit(format_args!("return: {}", #return_value));
+                },
+                (Exit::ReturnUnit, None) => quote! {
+                    __procon_lg_depth_guard.print_exit(format_args!("return"));
+                },
+                (_, None) => quote! {
+                    __procon_lg_depth_guard.print_exit(format_args!(""));
+                },
+            };
+        }
+
+        let suffix = suffix.unwrap_or_else(|| quote! { "" });
+        let text = match (exit, return_value) {
+            (_, Some(return_value)) => quote! {
+                format_args!(
+                    "return: {}{}{}",
+                    #return_value,
+                    procon_lg::Painted(procon_lg::Role::Args, &exit_args_str),
+                    #suffix
+                )
+            },
+            (Exit::ReturnUnit, None) => quote! {
+                format_args!(
+                    "return{}{}",
+                    procon_lg::Painted(procon_lg::Role::Args, &exit_args_str),
+                    #suffix
+                )
+            },
+            // Every exit argument is prefixed with ", ", which has nothing to follow here
+            (_, None) => quote! {
+                format_args!(
+                    "{}{}",
+                    procon_lg::Painted(
+                        procon_lg::Role::Args,
+                        exit_args_str.strip_prefix(", ").unwrap_or_default()
+                    ),
+                    #suffix
+                )
+            },
+        };
+        quote! {
+            {
+                let mut exit_args_str = String::new();
+                #(#exit_arg_exprs)*
+                __procon_lg_depth_guard.print_exit(#text);
             }
         }
     }
 
+    /// Generate the expression displaying the inclusive and self time of the call
+    fn generate_time_suffix(&self) -> Option<proc_macro2::TokenStream> {
+        self.macro_args
+            .time
+            .then(|| quote! { __procon_lg_depth_guard.time_suffix() })
+    }
+
+    /// Generate the expression displaying the id of the call and its parent, e.g. ` #17←#12`
+    fn generate_call_id_suffix(&self) -> Option<proc_macro2::TokenStream> {
+        self.macro_args
+            .call_ids
+            .then(|| quote! { __procon_lg_depth_guard.call_id_suffix() })
+    }
+
+    /// Generate the expression displaying the line of the recursive call, e.g. `  @L8`
+    fn generate_call_site_suffix(&self) -> Option<proc_macro2::TokenStream> {
+        self.macro_args
+            .call_site
+            .then(|| quote! { __procon_lg_depth_guard.call_site_suffix() })
+    }
+
+    /// Generate the expression displaying watch expressions, e.g. ` [count:3, self.len():5]`
+    fn generate_watch_suffix(&self) -> Option<proc_macro2::TokenStream> {
+        let watch = &self.macro_args.watch;
+        if watch.is_empty() {
+            return None;
+        }
+        let format_str = format!(" [{}]", vec!["{}:{:?}"; watch.len()].join(", "));
+        let names = watch.iter().map(|expr| compact_source(&quote! { #expr }));
+        Some(quote! {
+            format_args!(#format_str, #(#names, #watch),*)
+        })
+    }
+
+    /// Generate the expression displaying a return value for output
+    fn generate_return_value(&self, value: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        match &self.macro_args.return_formatter {
+            None => quote! { format_args!("{:?}", #value) },
+            Some(ReturnFormatter::Preset(preset)) => preset_format_expr(*preset, value),
+            Some(ReturnFormatter::Expression(formatter)) => quote! {
+                {
+                    let x = &#value;
+                    #formatter
+                }
+            },
+            Some(ReturnFormatter::Function(path)) => quote! { #path(&#value) },
+        }
+    }
+
+    /// Generate snapshots of `diff` arguments taken on entry
+    fn generate_arg_snapshots(&self) -> Vec<proc_macro2::TokenStream> {
+        self.extract_printable_args()
+            .iter()
+            .filter(|(_, _, attrs)| attrs.get_timing() == ShowTiming::Diff)
+            .map(|(ident_token, type_token, attrs)| {
+                let snapshot = snapshot_ident(ident_token);
+                let format_expr = arg_format_expr(ident_token, type_token, attrs);
+                quote! {
+                    let #snapshot = if __procon_lg_depth_guard.is_visible() {
+                        (#format_expr).to_string()
+                    } else {
+                        String::new()
+                    };
+                }
+            })
+            .collect()
+    }
+
     /// Generate argument format expressions
     fn generate_arg_format_expressions(&self) -> Vec<proc_macro2::TokenStream> {
         let printable_args = self.extract_printable_args();
 
         printable_args
             .iter()
+            .filter(|(_, _, attrs)| attrs.should_print_on_entry())
             .map(|(ident_token, type_token, attrs)| {
-                let format_expr = if let Some(formatter) = attrs.get_custom_formatter() {
-                    quote! {
-                        (|x: &#type_token| #formatter)(&#ident_token)
-                    }
+                let format_expr = if attrs.get_timing() == ShowTiming::Diff {
+                    let snapshot = snapshot_ident(ident_token);
+                    quote! { #snapshot }
                 } else {
-                    quote! {
-                        format!("{:?}", #ident_token)
-                    }
+                    arg_format_expr(ident_token, type_token, attrs)
                 };
                 let arg_name_str = ident_token.to_string();
                 quote! {
@@ -109,6 +375,35 @@ impl CodeGenerator {
             .collect()
     }
 
+    /// Generate format expressions of arguments shown on the return line
+    ///
+    /// Each argument is appended to `exit_args_str` prefixed with ", ".
+    fn generate_exit_arg_format_expressions(&self) -> Vec<proc_macro2::TokenStream> {
+        let printable_args = self.extract_printable_args();
+
+        printable_args
+            .iter()
+            .filter(|(_, _, attrs)| attrs.should_print_on_exit())
+            .map(|(ident_token, type_token, attrs)| {
+                let format_expr = arg_format_expr(ident_token, type_token, attrs);
+                let arg_name_str = ident_token.to_string();
+                if attrs.get_timing() == ShowTiming::Diff {
+                    let snapshot = snapshot_ident(ident_token);
+                    quote! {
+                        let after = (#format_expr).to_string();
+                        if after != #snapshot {
+                            std::fmt::Write::write_fmt(&mut exit_args_str, format_args!(", {}:{} {} {}", #arg_name_str, #snapshot, __procon_lg_depth_guard.arrow(), after)).unwrap();
+                        }
+                    }
+                } else {
+                    quote! {
+                        std::fmt::Write::write_fmt(&mut exit_args_str, format_args!(", {}:{}", #arg_name_str, #format_expr)).unwrap();
+                    }
+                }
+            })
+            .collect()
+    }
+
     /// Generate complete code
     pub fn generate(&self) -> proc_macro2::TokenStream {
         let fn_name = &self.input_fn.sig.ident;
@@ -118,8 +413,23 @@ impl CodeGenerator {
         let fn_vis = &self.input_fn.vis;
         let mut fn_block = self.input_fn.block.clone();
 
+        let keyed_by_args = self.macro_args.detect_cycle || self.macro_args.report_overlap;
+        if keyed_by_args && self.generate_arg_format_expressions().is_empty() {
+            return syn::Error::new_spanned(
+                fn_name,
+                "detect_cycle and report_overlap require at least one argument shown in the call header",
+            )
+            .to_compile_error();
+        }
+
         // Transform recursive calls and print-like macros
-        let mut visitor = Visitor;
+        let mut visitor = Visitor::new(
+            self.generate_exit_output(Exit::Return),
+            self.generate_exit_output(Exit::ReturnUnit),
+        );
+        if self.macro_args.call_site {
+            visitor = visitor.with_call_sites(fn_name.clone());
+        }
         visitor.visit_block_mut(&mut fn_block);
 
         // Extract argument information
@@ -128,29 +438,59 @@ impl CodeGenerator {
         let (impl_generics, _, where_clause) = fn_generics.split_for_impl();
 
         // Generate code components
-        let helper_macros = Self::generate_helper_macros();
+        let depth_guard = self.generate_depth_guard(fn_name);
         let recursion_check = self.generate_recursion_check(fn_name);
-        let return_output = self.generate_return_output();
+        let limit_checks = self.generate_limit_checks(fn_name);
+        let return_output = self.generate_exit_output(Exit::End);
+        let arg_snapshots = self.generate_arg_snapshots();
         let arg_format_exprs = self.generate_arg_format_expressions();
+        let header_suffix = concat_displays(
+            [
+                self.generate_watch_suffix(),
+                self.generate_call_id_suffix(),
+                self.generate_call_site_suffix(),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+        )
+        .unwrap_or_else(|| quote! { "" });
+        // Arguments of every call are needed to detect cycles and overlaps, not only of visible ones
+        let render_args = if keyed_by_args {
+            quote! {}
+        } else {
+            quote! { if __procon_lg_depth_guard.is_visible() }
+        };
+        let cycle_check = if self.macro_args.detect_cycle {
+            quote! { __procon_lg_depth_guard.check_cycle(); }
+        } else {
+            quote! {}
+        };
 
         quote! {
             #fn_vis #fn_unsafety fn #fn_name #impl_generics (#outer_fn_args) #fn_return_type #where_clause {
                 use procon_lg::DepthGuard;
 
-                #helper_macros
-
-                let __procon_lg_depth_guard = DepthGuard::new();
+                #depth_guard
                 #recursion_check
 
-                let mut args_str = String::new();
-                #(#arg_format_exprs)*
+                #(#arg_snapshots)*
+                #render_args {
+                    let mut args_str = __procon_lg_depth_guard.args_buffer();
+                    #(#arg_format_exprs)*
 
-                eprintln!(
-                    "{}{}({})",
-                    "│ ".repeat(__procon_lg_depth_guard.current_depth()),
-                    stringify!(#fn_name),
-                    args_str
-                );
+                    if __procon_lg_depth_guard.is_visible() {
+                        __procon_lg_depth_guard.print_header(format_args!(
+                            "{}({}){}",
+                            procon_lg::Painted(procon_lg::Role::Name, stringify!(#fn_name)),
+                            procon_lg::Painted(procon_lg::Role::Args, &args_str),
+                            #header_suffix
+                        ));
+                    }
+                    __procon_lg_depth_guard.set_args(args_str);
+                }
+                #cycle_check
+                #limit_checks
 
                 let ans = #fn_block;
 
@@ -237,6 +577,122 @@ impl CodeGenerator {
     }
 }
 
+/// Concatenate expressions displaying parts of a line, if any
+fn concat_displays(parts: Vec<proc_macro2::TokenStream>) -> Option<proc_macro2::TokenStream> {
+    match parts.len() {
+        0 => None,
+        1 => parts.into_iter().next(),
+        n => {
+            let format_str = "{}".repeat(n);
+            Some(quote! { format_args!(#format_str, #(#parts),*) })
+        }
+    }
+}
+
+/// Generate the format expression of an argument
+fn arg_format_expr(
+    ident_token: &proc_macro2::TokenStream,
+    type_token: &proc_macro2::TokenStream,
+    attrs: &ArgAttributes,
+) -> proc_macro2::TokenStream {
+    if let Some(preset) = attrs.get_preset() {
+        preset_format_expr(preset, ident_token)
+    } else if let Some(formatter) = attrs.get_custom_formatter() {
+        quote! {
+            (|x: &#type_token| #formatter)(&#ident_token)
+        }
+    } else {
+        quote! {
+            format_args!("{:?}", #ident_token)
+        }
+    }
+}
+
+/// Name of the local holding the rendering of a `diff` argument on entry
+fn snapshot_ident(ident_token: &proc_macro2::TokenStream) -> syn::Ident {
+    quote::format_ident!("__lg_snapshot_{}", ident_token.to_string())
+}
+
+/// Generate the format expression of a built-in formatter preset
+pub(crate) fn preset_format_expr(
+    preset: FormatPreset,
+    value: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    match preset {
+        FormatPreset::Hex => quote! { format_args!("{:#x}", #value) },
+        FormatPreset::Bin { width: None } => quote! { format_args!("{:b}", #value) },
+        FormatPreset::Bin { width: Some(width) } => {
+            quote! { format_args!("{:0width$b}", #value, width = #width) }
+        }
+        FormatPreset::Len => quote! { format_args!("len={}", #value.len()) },
+        FormatPreset::Display => quote! { format_args!("{}", #value) },
+        FormatPreset::Pretty => quote! { format_args!("{:#?}", #value) },
+        FormatPreset::Bits => quote! { procon_lg::Bits(&#value) },
+    }
+}
+
+/// Render tokens as compact source text, e.g. `dp[i][j]` rather than `dp [i] [j]`
+fn compact_source(tokens: &proc_macro2::TokenStream) -> String {
+    let source = tokens.to_string();
+    let chars: Vec<char> = source.chars().collect();
+    let mut compact = String::with_capacity(source.len());
+    let mut in_string = false;
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '"' && !compact.ends_with('\\') {
+            in_string = !in_string;
+        }
+        if c == ' ' && !in_string {
+            let prev = compact.chars().last();
+            let next = chars.get(i + 1).copied();
+            let after_next = chars[(i + 2).min(chars.len())..]
+                .iter()
+                .copied()
+                .find(|&c| c != ' ');
+            let is_callee =
+                |c: char| c.is_alphanumeric() || matches!(c, '_' | ')' | ']' | '!' | '>');
+            let glued = matches!(next, Some('.' | ',' | ')' | ']' | '?' | ';'))
+                || matches!(prev, Some('.' | '(' | '['))
+                || (matches!(next, Some('(' | '[')) && prev.is_some_and(is_callee))
+                || (next == Some('!') && matches!(after_next, Some('(' | '[' | '{')))
+                || (next == Some(':') && after_next == Some(':'))
+                || compact.ends_with("::");
+            if glued {
+                continue;
+            }
+        }
+        compact.push(c);
+    }
+
+    compact
+}
+
 fn is_custom_attr(attr: &Attribute) -> bool {
     attr.path().is_ident("show")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compact_source() {
+        let cases = [
+            (quote! { dp[i][j] }, "dp[i][j]"),
+            (quote! { self.items.last() }, "self.items.last()"),
+            (
+                quote! { CALLS.load(Ordering::Relaxed) },
+                "CALLS.load(Ordering::Relaxed)",
+            ),
+            (quote! { l + 1 == r }, "l + 1 == r"),
+            (
+                quote! { format!("{} {}", a, b) },
+                "format!(\"{} {}\", a, b)",
+            ),
+            (quote! { x != y }, "x != y"),
+        ];
+        for (tokens, expected) in cases {
+            assert_eq!(compact_source(&tokens), expected);
+        }
+    }
+}
@@ -20,13 +20,62 @@ use macro_args::MacroArgs;
 ///
 /// # Options
 ///
-/// - `no_return`: Disable return value output
+/// - `show_return`: Enable return value output
+/// - `show_return(expr)`: Enable return value output with custom formatter expression,
+///   where `x` is a reference to the return value; built-in presets are also accepted
+/// - `show_return = path`: Enable return value output formatted by `path(&value)`
+/// - `watch(expr, ...)`: Print the values of expressions on entry and on every exit
+/// - `when = expr`: Print only calls for which `expr` (which may refer to the arguments) holds
+/// - `focus = expr`: Print only the subtrees of calls for which `expr` holds, across all
+///   instrumented functions, with depth rebased to zero
+/// - `max_depth = K`: Hide calls at depth K or deeper, replacing each hidden subtree with a
+///   summary line; `procon_lg::set_max_depth` sets the same limit for all functions at runtime
+/// - `children(first = N, last = M)`: Print only the first N and the last M child calls of each
+///   call, replacing the others with a summary line; `procon_lg::set_children_limit` sets the
+///   same limit for all functions at runtime
 /// - `recursion_limit = N`: Set maximum recursion depth limit (must be > 0)
+/// - `call_limit = N`: Panic with the call stack once more than N instrumented calls are made
+///   within the outermost call
+/// - `time_limit_ms = T`: Panic with the call stack once the outermost call has run for more
+///   than T milliseconds
+/// - `on_limit = ...`: Behaviour when one of the limits above is exceeded: `panic` (default),
+///   `truncate` to keep running without output beyond the limit, `warn` to keep running after
+///   printing a warning once, or an expression returned as a fallback value
+/// - `detect_cycle`: Warn with the cycle when a call is made with the same `#[show]` arguments
+///   as an active call of the same function, once per outermost call
+/// - `report_overlap`: When the outermost call returns, list the `#[show]` argument tuples
+///   evaluated more than once, with the number of calls spent in their repeated subtrees
+/// - `stats`: When the outermost call returns, print a table of calls, maximum depth, early
+///   returns and leaf calls per function; `procon_lg::set_stats` enables it for all functions
+/// - `time`: Measure the wall time of each call, print its inclusive and self time on the
+///   return line, and list the calls with the largest self time when the outermost call returns
+/// - `call_ids`: Mark call headers and return lines with the id of the call and of its parent,
+///   e.g. `#17←#12`
+/// - `call_site`: Show the source line of the recursive call in the header of the called function,
+///   e.g. `fib(n:3)  @L8`; compilers before Rust 1.88 have no source lines and show nothing
+/// - `compact`: Print calls without instrumented children and messages on one line, e.g.
+///   `fib(n:1) → 1`; `procon_lg::set_compact` enables it for all functions at runtime
+/// - `style = unicode | ascii | depth`: Drawing style of the call tree, overriding the one set
+///   for all functions with `procon_lg::set_tree_style`
+/// - `max_guides = K`: Draw at most K guides per line, with a marker like `… (depth 9732)` for
+///   the omitted ones; `procon_lg::set_max_guides` sets the same limit for all functions
 ///
 /// # Attributes
 ///
 /// - `#[show]`: Include argument in debug output with default formatting
 /// - `#[show(expr)]`: Include argument in debug output with custom formatter expression
+/// - `#[show(preset)]`: Include argument in debug output with a built-in formatter:
+///   `hex`, `bin`, `bin = N`, `len`, `display`, `pretty` or `bits`
+/// - `#[show(after)]`: Show argument on the return line instead of the call header
+/// - `#[show(both)]`: Show argument in the call header and on the return line
+/// - `#[show(diff)]`: Show argument in the call header, and as `before → after` on the
+///   return line if it changed
+/// - `#[show(len, diff)]`: A preset and a timing can be given together
+///
+/// A bare name of a preset or timing is read as such, not as a custom formatter expression;
+/// wrap an expression of that name in parentheses, e.g. `#[show((len))]`.
+/// - `#[show] let ...;`: Print the bindings of a `let` statement in the function body;
+///   presets and custom formatter expressions (with the value bound to `x`) are also accepted
 #[proc_macro_attribute]
 pub fn lg_recur(attr: TokenStream, item: TokenStream) -> TokenStream {
     let macro_args: MacroArgs = if attr.is_empty() {
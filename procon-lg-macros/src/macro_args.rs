@@ -1,10 +1,85 @@
 use syn::parse::Parse;
 
+use crate::arg_attrs::FormatPreset;
+
 /// Structure representing macro arguments
+// Every flag option of the macro is a bool
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Default)]
 pub struct MacroArgs {
     pub recursion_limit: Option<usize>,
     pub show_return: bool,
+    pub return_formatter: Option<ReturnFormatter>,
+    pub watch: Vec<syn::Expr>,
+    pub when: Option<syn::Expr>,
+    pub focus: Option<syn::Expr>,
+    pub max_depth: Option<usize>,
+    pub children_limit: Option<(usize, usize)>,
+    pub call_limit: Option<usize>,
+    pub time_limit_ms: Option<u64>,
+    pub on_limit: LimitAction,
+    pub detect_cycle: bool,
+    pub report_overlap: bool,
+    pub stats: bool,
+    pub time: bool,
+    pub call_ids: bool,
+    pub call_site: bool,
+    pub compact: bool,
+    /// Variant of `procon_lg::TreeStyle`
+    pub style: Option<syn::Ident>,
+    pub max_guides: Option<usize>,
+}
+
+/// Behaviour when `recursion_limit`, `call_limit` or `time_limit_ms` is exceeded
+#[derive(Default)]
+pub enum LimitAction {
+    /// on_limit = panic - Panic with the call stack
+    #[default]
+    Panic,
+    /// on_limit = truncate - Keep running, hiding the output of calls beyond the limit
+    Truncate,
+    /// on_limit = warn - Keep running, printing a warning the first time the limit is exceeded
+    Warn,
+    /// on_limit = expr - Return the expression from calls beyond the limit
+    Fallback(Box<syn::Expr>),
+}
+
+impl Parse for LimitAction {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let expr: syn::Expr = input.parse()?;
+        if let syn::Expr::Path(path) = &expr {
+            if let Some(ident) = path.path.get_ident() {
+                match ident.to_string().as_str() {
+                    "panic" => return Ok(LimitAction::Panic),
+                    "truncate" => return Ok(LimitAction::Truncate),
+                    "warn" => return Ok(LimitAction::Warn),
+                    _ => {}
+                }
+            }
+        }
+        Ok(LimitAction::Fallback(Box::new(expr)))
+    }
+}
+
+/// Custom formatter for the return value
+pub enum ReturnFormatter {
+    /// show_return(preset) - Built-in formatter preset
+    Preset(FormatPreset),
+    /// show_return(expr) - Formatter expression, with the return value bound to `x`
+    Expression(syn::Expr),
+    /// show_return = path - Function taking a reference to the return value
+    Function(syn::Path),
+}
+
+impl Parse for ReturnFormatter {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let fork = input.fork();
+        if fork.parse::<FormatPreset>().is_ok() && fork.is_empty() {
+            Ok(ReturnFormatter::Preset(input.parse()?))
+        } else {
+            Ok(ReturnFormatter::Expression(input.parse()?))
+        }
+    }
 }
 
 impl Parse for MacroArgs {
@@ -16,19 +91,92 @@ impl Parse for MacroArgs {
 
             match ident.to_string().as_str() {
                 "recursion_limit" => {
-                    input.parse::<syn::Token![=]>()?;
-                    let limit: syn::LitInt = input.parse()?;
-                    let limit_value = limit.base10_parse::<usize>()?;
-                    if limit_value == 0 {
-                        return Err(syn::Error::new(
-                            limit.span(),
-                            "recursion_limit must be greater than 0",
-                        ));
-                    }
-                    args.recursion_limit = Some(limit_value);
+                    args.recursion_limit = Some(parse_limit(input, "recursion_limit")?);
                 }
                 "show_return" => {
                     args.show_return = true;
+                    if input.peek(syn::token::Paren) {
+                        let content;
+                        syn::parenthesized!(content in input);
+                        args.return_formatter = Some(content.parse()?);
+                    } else if input.peek(syn::Token![=]) {
+                        input.parse::<syn::Token![=]>()?;
+                        args.return_formatter = Some(ReturnFormatter::Function(input.parse()?));
+                    }
+                }
+                "watch" => {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    let exprs = content.parse_terminated(syn::Expr::parse, syn::Token![,])?;
+                    args.watch.extend(exprs);
+                }
+                "when" => {
+                    input.parse::<syn::Token![=]>()?;
+                    args.when = Some(input.parse()?);
+                }
+                "focus" => {
+                    input.parse::<syn::Token![=]>()?;
+                    args.focus = Some(input.parse()?);
+                }
+                "max_depth" => {
+                    input.parse::<syn::Token![=]>()?;
+                    let max_depth: syn::LitInt = input.parse()?;
+                    args.max_depth = Some(max_depth.base10_parse::<usize>()?);
+                }
+                "children" => {
+                    args.children_limit = Some(parse_children_limit(input)?);
+                }
+                "call_limit" => {
+                    args.call_limit = Some(parse_limit(input, "call_limit")?);
+                }
+                "time_limit_ms" => {
+                    args.time_limit_ms = Some(parse_limit(input, "time_limit_ms")?);
+                }
+                "detect_cycle" => {
+                    args.detect_cycle = true;
+                }
+                "report_overlap" => {
+                    args.report_overlap = true;
+                }
+                "stats" => {
+                    args.stats = true;
+                }
+                "time" => {
+                    args.time = true;
+                }
+                "call_ids" => {
+                    args.call_ids = true;
+                }
+                "call_site" => {
+                    args.call_site = true;
+                }
+                "compact" => {
+                    args.compact = true;
+                }
+                "max_guides" => {
+                    input.parse::<syn::Token![=]>()?;
+                    let max_guides: syn::LitInt = input.parse()?;
+                    args.max_guides = Some(max_guides.base10_parse::<usize>()?);
+                }
+                "style" => {
+                    input.parse::<syn::Token![=]>()?;
+                    let style: syn::Ident = input.parse()?;
+                    let variant = match style.to_string().as_str() {
+                        "unicode" => "Unicode",
+                        "ascii" => "Ascii",
+                        "depth" => "Depth",
+                        _ => {
+                            return Err(syn::Error::new(
+                                style.span(),
+                                "expected `unicode`, `ascii` or `depth`",
+                            ))
+                        }
+                    };
+                    args.style = Some(syn::Ident::new(variant, style.span()));
+                }
+                "on_limit" => {
+                    input.parse::<syn::Token![=]>()?;
+                    args.on_limit = input.parse()?;
                 }
                 _ => return Err(syn::Error::new(ident.span(), "unknown argument")),
             }
@@ -41,3 +189,42 @@ impl Parse for MacroArgs {
         Ok(args)
     }
 }
+
+/// Parse `= N` for a limit option, rejecting zero
+fn parse_limit<N>(input: syn::parse::ParseStream, name: &str) -> syn::Result<N>
+where
+    N: std::str::FromStr + PartialEq + From<u8>,
+    N::Err: std::fmt::Display,
+{
+    input.parse::<syn::Token![=]>()?;
+    let limit: syn::LitInt = input.parse()?;
+    let limit_value = limit.base10_parse::<N>()?;
+    if limit_value == N::from(0) {
+        return Err(syn::Error::new(
+            limit.span(),
+            format!("{name} must be greater than 0"),
+        ));
+    }
+    Ok(limit_value)
+}
+
+/// Parse `(first = N, last = M)` for the `children` option
+fn parse_children_limit(input: syn::parse::ParseStream) -> syn::Result<(usize, usize)> {
+    let content;
+    syn::parenthesized!(content in input);
+    let (mut first, mut last) = (0, 0);
+    while !content.is_empty() {
+        let key: syn::Ident = content.parse()?;
+        content.parse::<syn::Token![=]>()?;
+        let value: syn::LitInt = content.parse()?;
+        match key.to_string().as_str() {
+            "first" => first = value.base10_parse::<usize>()?,
+            "last" => last = value.base10_parse::<usize>()?,
+            _ => return Err(syn::Error::new(key.span(), "expected `first` or `last`")),
+        }
+        if content.peek(syn::Token![,]) {
+            content.parse::<syn::Token![,]>()?;
+        }
+    }
+    Ok((first, last))
+}
@@ -1,13 +1,56 @@
-use quote::ToTokens;
+use quote::{quote, ToTokens};
 use syn::visit_mut::{self, VisitMut};
-use syn::{Expr, ExprReturn};
+use syn::{Block, Expr, ExprReturn, Local, PatIdent, Stmt};
+
+use crate::{arg_attrs::ArgAttributes, codegen::preset_format_expr};
 
 /// Visitor for AST transformation
 ///
 /// Call only [`Visitor::visit_block_mut`]!
-pub struct Visitor;
+pub struct Visitor {
+    /// Output for `return expr`, with the value in `__lg_return_val`
+    return_output: proc_macro2::TokenStream,
+    /// Output for `return`
+    unit_return_output: proc_macro2::TokenStream,
+    /// Name of the function, if the call sites of its recursive calls are recorded
+    call_site_fn: Option<syn::Ident>,
+}
+
+impl Visitor {
+    /// Create a visitor emitting the given output on early returns
+    pub fn new(
+        return_output: proc_macro2::TokenStream,
+        unit_return_output: proc_macro2::TokenStream,
+    ) -> Self {
+        Visitor {
+            return_output,
+            unit_return_output,
+            call_site_fn: None,
+        }
+    }
+
+    /// Record the source line of each recursive call of the function `fn_name`
+    pub fn with_call_sites(mut self, fn_name: syn::Ident) -> Self {
+        self.call_site_fn = Some(fn_name);
+        self
+    }
+}
 
 impl VisitMut for Visitor {
+    /// Log `#[show] let` bindings after their statements
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        visit_mut::visit_block_mut(self, block);
+
+        for mut stmt in std::mem::take(&mut block.stmts) {
+            let print = match &mut stmt {
+                Stmt::Local(local) => transform_local(local),
+                _ => None,
+            };
+            block.stmts.push(stmt);
+            block.stmts.extend(print);
+        }
+    }
+
     /// Transform print-like macros
     fn visit_macro_mut(&mut self, mac: &mut syn::Macro) {
         let path = mac.path.to_token_stream().to_string();
@@ -26,36 +69,142 @@ impl VisitMut for Visitor {
 
         // Then transform this expression if it's a return
         if let Expr::Return(expr_return) = expr {
-            *expr = transform_return_expr(expr_return);
+            *expr =
+                transform_return_expr(expr_return, &self.return_output, &self.unit_return_output);
+        }
+
+        if let Some(fn_name) = &self.call_site_fn {
+            record_call_site(expr, fn_name);
         }
     }
 }
 
+/// Record the source line of a recursive call for the header of the called function
+///
+/// The line is recorded while evaluating the last argument, after any nested calls in the
+/// arguments, so that it is taken by this call. Only method calls on `self` are recursive calls.
+fn record_call_site(expr: &mut Expr, fn_name: &syn::Ident) {
+    let (line, args) = match expr {
+        Expr::Call(call) => {
+            let Expr::Path(path) = &*call.func else {
+                return;
+            };
+            match path.path.segments.last() {
+                Some(segment) if segment.ident == *fn_name => {
+                    (segment.ident.span().start().line, &mut call.args)
+                }
+                _ => return,
+            }
+        }
+        Expr::MethodCall(call) if call.method == *fn_name && is_self(&call.receiver) => {
+            (call.method.span().start().line, &mut call.args)
+        }
+        _ => return,
+    };
+    // Spans have no source locations before Rust 1.88
+    if line == 0 {
+        return;
+    }
+    let name = fn_name.to_string();
+    match args.pop() {
+        Some(last) => {
+            let last = last.into_value();
+            args.push(syn::parse_quote! { procon_lg::at_call_site(#name, #line, #last) });
+        }
+        None => {
+            *expr = syn::parse_quote! {
+                {
+                    procon_lg::at_call_site(#name, #line, ());
+                    #expr
+                }
+            };
+        }
+    }
+}
+
+/// Whether an expression is the `self` receiver
+fn is_self(expr: &Expr) -> bool {
+    matches!(expr, Expr::Path(path) if path.path.is_ident("self"))
+}
+
 /// Transform `eprintln!` macro
 fn transform_eprintln_macro(tokens: &proc_macro2::TokenStream) -> syn::Macro {
     if tokens.is_empty() {
         syn::parse_quote! {
-            eprintln!("{}", "│ ".repeat(__procon_lg_depth_guard.current_depth() + 1))
+            procon_lg::__lg_print!(__procon_lg_depth_guard, "")
         }
     } else {
         syn::parse_quote! {
-            __lg_print!(eprintln, __procon_lg_depth_guard.current_depth() + 1, #tokens)
+            procon_lg::__lg_print!(__procon_lg_depth_guard, #tokens)
         }
     }
 }
 
+/// Strip `#[show]` from a `let` statement and generate the statement logging its bindings
+fn transform_local(local: &mut Local) -> Option<Stmt> {
+    let attrs = ArgAttributes::from_attrs(&local.attrs);
+    if !attrs.should_print() {
+        return None;
+    }
+    local.attrs.retain(|attr| !attr.path().is_ident("show"));
+    local.init.as_ref()?;
+
+    let mut collector = BindingCollector::default();
+    collector.visit_pat_mut(&mut local.pat.clone());
+
+    let format_str = collector
+        .idents
+        .iter()
+        .map(|ident| format!("{ident} = {{}}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let format_str = format!("let {format_str}");
+    let format_exprs = collector.idents.iter().map(|ident| {
+        let value = quote! { #ident };
+        if let Some(preset) = attrs.get_preset() {
+            preset_format_expr(preset, &value)
+        } else if let Some(formatter) = attrs.get_custom_formatter() {
+            quote! {
+                {
+                    let x = &#value;
+                    #formatter
+                }
+            }
+        } else {
+            quote! { format_args!("{:?}", #value) }
+        }
+    });
+
+    Some(syn::parse_quote! {
+        procon_lg::__lg_print!(__procon_lg_depth_guard, #format_str, #(#format_exprs),*);
+    })
+}
+
+/// Collects the identifiers bound by a pattern
+#[derive(Default)]
+struct BindingCollector {
+    idents: Vec<syn::Ident>,
+}
+
+impl VisitMut for BindingCollector {
+    fn visit_pat_ident_mut(&mut self, pat_ident: &mut PatIdent) {
+        self.idents.push(pat_ident.ident.clone());
+        visit_mut::visit_pat_ident_mut(self, pat_ident);
+    }
+}
+
 /// Transform return expression to include logging
-fn transform_return_expr(expr_return: &ExprReturn) -> Expr {
+fn transform_return_expr(
+    expr_return: &ExprReturn,
+    return_output: &proc_macro2::TokenStream,
+    unit_return_output: &proc_macro2::TokenStream,
+) -> Expr {
     match &expr_return.expr {
         Some(return_expr) => {
             syn::parse_quote! {
                 {
                     let __lg_return_val = #return_expr;
-                    eprintln!(
-                        "{}└ return: {:?}",
-                        "│ ".repeat(__procon_lg_depth_guard.current_depth()),
-                        __lg_return_val
-                    );
+                    #return_output
                     return __lg_return_val;
                 }
             }
@@ -63,10 +212,7 @@ fn transform_return_expr(expr_return: &ExprReturn) -> Expr {
         None => {
             syn::parse_quote! {
                 {
-                    eprintln!(
-                        "{}└ return",
-                        "│ ".repeat(__procon_lg_depth_guard.current_depth())
-                    );
+                    #unit_return_output
                     return;
                 }
             }
@@ -77,12 +223,11 @@ fn transform_return_expr(expr_return: &ExprReturn) -> Expr {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use quote::quote;
-    use syn::{parse_quote, Block};
+    use syn::parse_quote;
 
     #[test]
     fn test_println() {
-        let mut visitor = Visitor;
+        let mut visitor = Visitor::new(quote!(), quote!());
 
         let mut block: Block = parse_quote! {
             {
@@ -94,7 +239,87 @@ mod tests {
 
         let expected: Block = parse_quote! {
             {
-                __lg_print!(eprintln, __procon_lg_depth_guard.current_depth() + 1, "line1\nline2\nline3");
+                procon_lg::__lg_print!(__procon_lg_depth_guard, "line1\nline2\nline3");
+            }
+        };
+
+        assert_eq!(quote!(#block).to_string(), quote!(#expected).to_string());
+    }
+
+    #[test]
+    fn test_return() {
+        let mut visitor = Visitor::new(quote!(print_value(&__lg_return_val);), quote!());
+
+        let mut block: Block = parse_quote! {
+            {
+                return x;
+            }
+        };
+
+        visitor.visit_block_mut(&mut block);
+
+        let expected: Block = parse_quote! {
+            {
+                {
+                    let __lg_return_val = x;
+                    print_value(&__lg_return_val);
+                    return __lg_return_val;
+                };
+            }
+        };
+
+        assert_eq!(quote!(#block).to_string(), quote!(#expected).to_string());
+    }
+
+    #[test]
+    fn test_show_let() {
+        let mut visitor = Visitor::new(quote!(), quote!());
+
+        let mut block: Block = parse_quote! {
+            {
+                #[show]
+                let (lo, hi) = (l, r);
+                #[show(hex)]
+                let mask = 1 << lo;
+            }
+        };
+
+        visitor.visit_block_mut(&mut block);
+
+        let expected: Block = parse_quote! {
+            {
+                let (lo, hi) = (l, r);
+                procon_lg::__lg_print!(__procon_lg_depth_guard, "let lo = {}, hi = {}", format_args!("{:?}", lo), format_args!("{:?}", hi));
+                let mask = 1 << lo;
+                procon_lg::__lg_print!(__procon_lg_depth_guard, "let mask = {}", format_args!("{:#x}", mask));
+            }
+        };
+
+        assert_eq!(quote!(#block).to_string(), quote!(#expected).to_string());
+    }
+
+    #[test]
+    fn test_call_site() {
+        let fn_name: syn::Ident = parse_quote!(fib);
+        let mut visitor = Visitor::new(quote!(), quote!()).with_call_sites(fn_name);
+
+        let mut block: Block = syn::parse_str(
+            "{\n    fib(n - 1) + self.fib(fib(n - 2))\n    + fib() + other(n) + a.fib(n)\n}",
+        )
+        .unwrap();
+
+        visitor.visit_block_mut(&mut block);
+
+        let expected: Block = parse_quote! {
+            {
+                fib(procon_lg::at_call_site("fib", 2usize, n - 1))
+                    + self.fib(procon_lg::at_call_site("fib", 2usize, fib(procon_lg::at_call_site("fib", 2usize, n - 2))))
+                    + {
+                        procon_lg::at_call_site("fib", 3usize, ());
+                        fib()
+                    }
+                    + other(n)
+                    + a.fib(n)
             }
         };
 
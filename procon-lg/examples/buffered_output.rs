@@ -0,0 +1,36 @@
+use procon_lg::lg_recur;
+
+#[lg_recur]
+fn countdown(#[show] n: u32) {
+    println!("stdout: {n}");
+    if n > 0 {
+        countdown(n - 1);
+    }
+}
+
+#[lg_recur]
+fn get(a: &[u32], #[show] i: usize) -> u32 {
+    if i == 0 {
+        return a[a.len()];
+    }
+    get(a, i - 1)
+}
+
+fn main() {
+    // By default every trace line is flushed, in order with stdout
+    countdown(2);
+
+    std::panic::set_hook(Box::new(|info| {
+        if let Some(message) = info.payload().downcast_ref::<String>() {
+            eprintln!("panicked: {message}");
+        }
+    }));
+
+    // Buffered, the trace of each outermost call is written in one block when it returns
+    procon_lg::set_buffered_output(true);
+    countdown(2);
+
+    // The trace so far is written out before the panic message
+    let result = std::panic::catch_unwind(|| get(&[1, 2], 2));
+    println!("{}", result.is_err());
+}
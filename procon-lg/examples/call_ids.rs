@@ -0,0 +1,21 @@
+use procon_lg::lg_recur;
+
+#[lg_recur(show_return, call_ids)]
+fn fib(#[show] n: u32) -> u32 {
+    if n <= 1 {
+        return 1;
+    }
+    fib(n - 1) + fib(n - 2)
+}
+
+#[lg_recur(call_ids, watch(n % 2))]
+fn countdown(#[show] n: u32) {
+    if n > 0 {
+        countdown(n - 1);
+    }
+}
+
+fn main() {
+    println!("{}", fib(3));
+    countdown(1);
+}
@@ -0,0 +1,29 @@
+use procon_lg::lg_recur;
+
+#[lg_recur(show_return, call_limit = 6)]
+fn fib(#[show] n: u32) -> u32 {
+    if n <= 1 {
+        1
+    } else {
+        fib(n - 1) + fib(n - 2)
+    }
+}
+
+#[lg_recur(time_limit_ms = 60_000)]
+fn countdown(#[show] n: u32) {
+    if n > 0 {
+        countdown(n - 1);
+    }
+}
+
+fn main() {
+    countdown(2);
+
+    std::panic::set_hook(Box::new(|info| {
+        if let Some(message) = info.payload().downcast_ref::<String>() {
+            eprintln!("panicked: {message}");
+        }
+    }));
+    let result = std::panic::catch_unwind(|| fib(5));
+    println!("{result:?}");
+}
@@ -0,0 +1,47 @@
+use procon_lg::lg_recur;
+
+#[lg_recur(show_return, call_site)]
+fn knapsack(items: &[(usize, u64)], #[show] i: usize, #[show] cap: usize) -> u64 {
+    if i == items.len() {
+        return 0;
+    }
+    let (weight, value) = items[i];
+    let skip = knapsack(items, i + 1, cap);
+    if weight <= cap {
+        skip.max(knapsack(items, i + 1, cap - weight) + value)
+    } else {
+        skip
+    }
+}
+
+/// `a[0].min(..)` is the method of `usize`, not a recursive call
+#[lg_recur(show_return, call_site)]
+fn min(#[show] a: &[usize]) -> usize {
+    if a.len() == 1 {
+        return a[0];
+    }
+    a[0].min(min(&a[1..]))
+}
+
+struct Tree {
+    children: Vec<Vec<usize>>,
+}
+
+impl Tree {
+    #[lg_recur(show_return, call_site)]
+    fn size(&self, #[show] v: usize) -> usize {
+        1 + self.children[v]
+            .iter()
+            .map(|&u| self.size(u))
+            .sum::<usize>()
+    }
+}
+
+fn main() {
+    println!("{}", knapsack(&[(2, 3), (1, 2)], 0, 2));
+    let tree = Tree {
+        children: vec![vec![1, 2], vec![], vec![]],
+    };
+    println!("{}", tree.size(0));
+    println!("{}", min(&[3, 1]));
+}
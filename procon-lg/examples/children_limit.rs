@@ -0,0 +1,55 @@
+use procon_lg::lg_recur;
+
+#[lg_recur(children(first = 2, last = 1))]
+fn dfs(adj: &[Vec<usize>], #[show] v: usize, p: usize) {
+    for &u in &adj[v] {
+        if u != p {
+            dfs(adj, u, v);
+        }
+    }
+}
+
+#[lg_recur(show_return)]
+fn count(#[show] depth: u32) -> u32 {
+    if depth == 0 {
+        return 1;
+    }
+    (0..4).map(|_| count(depth - 1)).sum()
+}
+
+// Hidden children print nothing, so they are neither shown nor counted as more calls
+#[lg_recur(when = n == 2, children(first = 1, last = 1))]
+fn branch(#[show] n: u32) {
+    if n > 0 {
+        (0..3).for_each(|_| branch(n - 1));
+    }
+}
+
+#[lg_recur(max_depth = 1, children(first = 1, last = 1))]
+fn shallow(#[show] n: u32) {
+    if n > 0 {
+        (0..3).for_each(|_| shallow(n - 1));
+    }
+}
+
+fn main() {
+    // A star with center 0, and a path 1 - 7 - 8 below leaf 1
+    let mut adj = vec![vec![]; 9];
+    let mut add_edge = |u: usize, v: usize| {
+        adj[u].push(v);
+        adj[v].push(u);
+    };
+    for v in 1..7 {
+        add_edge(0, v);
+    }
+    add_edge(1, 7);
+    add_edge(7, 8);
+    dfs(&adj, 0, usize::MAX);
+
+    procon_lg::set_children_limit(Some((1, 1)));
+    println!("{}", count(2));
+    procon_lg::set_children_limit(None);
+
+    branch(2);
+    shallow(2);
+}
@@ -0,0 +1,19 @@
+use procon_lg::{lg_recur, ColorChoice};
+
+#[lg_recur(show_return)]
+fn fib(#[show] n: u32) -> u32 {
+    if n <= 1 {
+        eprintln!("base case");
+        return 1;
+    }
+    fib(n - 1) + fib(n - 2)
+}
+
+fn main() {
+    // Stderr is not a terminal when the output is captured
+    procon_lg::set_color(ColorChoice::Auto);
+    println!("{}", fib(1));
+
+    procon_lg::set_color(ColorChoice::Always);
+    println!("{}", fib(2));
+}
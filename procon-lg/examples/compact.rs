@@ -0,0 +1,60 @@
+use procon_lg::lg_recur;
+
+#[lg_recur(show_return, compact)]
+fn fib(#[show] n: u32) -> u32 {
+    if n <= 1 {
+        return 1;
+    }
+    fib(n - 1) + fib(n - 2)
+}
+
+#[lg_recur(compact)]
+fn visit(#[show] v: usize, #[show(after)] order: &mut Vec<usize>) {
+    order.push(v);
+    if v < 2 {
+        visit(2 * v + 1, order);
+    }
+    if v == 1 {
+        eprintln!("visited {v}");
+    }
+}
+
+struct Stack {
+    v: Vec<u32>,
+}
+
+impl Stack {
+    // The ids end the header of a compact line, and are not repeated after the watch values
+    #[lg_recur(show_return, compact, call_ids, watch(self.v.len()))]
+    fn fill(&mut self, #[show] n: u32) -> usize {
+        self.v.push(n);
+        if n > 0 {
+            self.fill(n - 1)
+        } else {
+            self.v.len()
+        }
+    }
+}
+
+fn main() {
+    println!("{}", fib(3));
+
+    let mut order = Vec::new();
+    visit(0, &mut order);
+    println!("{order:?}");
+
+    let mut stack = Stack { v: Vec::new() };
+    println!("{}", stack.fill(1));
+
+    procon_lg::set_compact(true);
+    println!("{}", pow(3, 2));
+}
+
+#[lg_recur(show_return)]
+fn pow(#[show] a: u64, #[show] b: u32) -> u64 {
+    if b == 0 {
+        1
+    } else {
+        a * pow(a, b - 1)
+    }
+}
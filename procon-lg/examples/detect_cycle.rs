@@ -0,0 +1,34 @@
+use procon_lg::lg_recur;
+
+/// DFS on a graph with a cycle, missing the visited check
+#[lg_recur(detect_cycle, recursion_limit = 6)]
+fn dfs(adj: &[Vec<usize>], #[show] v: usize, depth: usize) -> usize {
+    adj[v]
+        .iter()
+        .map(|&u| dfs(adj, u, depth + 1))
+        .max()
+        .unwrap_or(depth)
+}
+
+/// Terminates as `k` decreases, though the shown arguments repeat
+#[lg_recur(detect_cycle)]
+fn repeat(#[show] n: usize, k: usize) -> usize {
+    if k == 0 {
+        return n;
+    }
+    repeat(n, k - 1)
+}
+
+fn main() {
+    let adj = vec![vec![1], vec![2, 3], vec![], vec![1]];
+
+    std::panic::set_hook(Box::new(|info| {
+        if let Some(message) = info.payload().downcast_ref::<String>() {
+            eprintln!("panicked: {message}");
+        }
+    }));
+    let result = std::panic::catch_unwind(|| dfs(&adj, 0, 0));
+    println!("{result:?}");
+
+    println!("{}", repeat(7, 2));
+}
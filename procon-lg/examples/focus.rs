@@ -0,0 +1,45 @@
+use procon_lg::lg_recur;
+
+#[lg_recur(show_return)]
+fn subtree_size(children: &[Vec<usize>], #[show] v: usize) -> usize {
+    1 + children[v]
+        .iter()
+        .map(|&u| subtree_size(children, u))
+        .sum::<usize>()
+}
+
+#[lg_recur(focus = v == 2)]
+fn dfs(children: &[Vec<usize>], #[show] v: usize) {
+    if children[v].len() > 1 {
+        eprintln!("size = {}", subtree_size(children, v));
+    }
+    for &u in &children[v] {
+        dfs(children, u);
+    }
+}
+
+#[lg_recur]
+fn countdown(#[show] n: u32) {
+    if n > 0 {
+        countdown(n - 1);
+    }
+}
+
+fn main() {
+    // 0 ─┬─ 1 ── 3
+    //    └─ 2 ─┬─ 4
+    //          └─ 5 ── 6
+    let children = vec![
+        vec![1, 2],
+        vec![3],
+        vec![4, 5],
+        vec![],
+        vec![],
+        vec![6],
+        vec![],
+    ];
+    dfs(&children, 0);
+
+    // Focus mode ends with the outermost call
+    countdown(1);
+}
@@ -0,0 +1,26 @@
+use procon_lg::{lg_recur, FoldedWeight};
+
+#[lg_recur]
+fn fib(#[show] n: u32) -> u32 {
+    if n <= 1 {
+        1
+    } else {
+        fib(n - 1) + fib(n - 2)
+    }
+}
+
+#[lg_recur]
+fn solve(#[show] n: u32) -> u32 {
+    fib(n) + fib(n - 1)
+}
+
+fn main() {
+    let path = std::env::temp_dir().join("procon_lg_folded_example.folded");
+    procon_lg::set_folded_output(Some(path.clone()), FoldedWeight::Calls);
+
+    println!("{}", solve(4));
+    // The file is written when the thread exits, so write it now to read it back
+    procon_lg::write_folded();
+    print!("{}", std::fs::read_to_string(&path).unwrap());
+    std::fs::remove_file(&path).unwrap();
+}
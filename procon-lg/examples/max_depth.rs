@@ -0,0 +1,27 @@
+use procon_lg::lg_recur;
+
+#[lg_recur(show_return, max_depth = 2)]
+fn fib(#[show] n: u32) -> u32 {
+    if n <= 1 {
+        1
+    } else {
+        fib(n - 1) + fib(n - 2)
+    }
+}
+
+#[lg_recur]
+fn walk(#[show] v: usize, n: usize) {
+    if 2 * v + 1 < n {
+        walk(2 * v + 1, n);
+        eprintln!("between children of {v}");
+        walk(2 * v + 2, n);
+    }
+}
+
+fn main() {
+    println!("{}", fib(5));
+
+    procon_lg::set_max_depth(Some(1));
+    walk(0, 15);
+    procon_lg::set_max_depth(None);
+}
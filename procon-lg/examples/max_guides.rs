@@ -0,0 +1,24 @@
+use procon_lg::lg_recur;
+
+/// Walk down a line graph
+#[lg_recur(show_return, max_guides = 3)]
+fn walk(#[show] v: usize, n: usize) -> usize {
+    if v + 1 == n {
+        return v;
+    }
+    walk(v + 1, n)
+}
+
+#[lg_recur]
+fn countdown(#[show] n: u32) {
+    if n > 0 {
+        countdown(n - 1);
+    }
+}
+
+fn main() {
+    println!("{}", walk(0, 6));
+
+    procon_lg::set_max_guides(Some(0));
+    countdown(2);
+}
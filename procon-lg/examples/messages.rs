@@ -0,0 +1,23 @@
+use procon_lg::lg_recur;
+
+// Messages of instrumented functions go through `procon_lg::__lg_print!`, so a macro of the
+// same name here is left alone
+macro_rules! __lg_print {
+    ($($args:tt)*) => {
+        println!("user macro: {}", format_args!($($args)*))
+    };
+}
+
+#[lg_recur(show_return)]
+fn sum(#[show] n: u32) -> u32 {
+    if n == 0 {
+        return 0;
+    }
+    eprintln!("adding {n}");
+    __lg_print!("adding {n}");
+    n + sum(n - 1)
+}
+
+fn main() {
+    println!("{}", sum(2));
+}
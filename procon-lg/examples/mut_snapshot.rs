@@ -0,0 +1,64 @@
+use procon_lg::lg_recur;
+
+#[lg_recur(show_return)]
+fn find(#[show(diff)] parent: &mut [usize], #[show] x: usize) -> usize {
+    if parent[x] == x {
+        return x;
+    }
+    let root = find(parent, parent[x]);
+    parent[x] = root;
+    root
+}
+
+#[lg_recur]
+fn dfs(#[show(after)] visited: &mut Vec<bool>, adj: &[Vec<usize>], #[show] v: usize) {
+    visited[v] = true;
+    for &u in &adj[v] {
+        if !visited[u] {
+            dfs(visited, adj, u);
+        }
+    }
+}
+
+#[lg_recur]
+fn quicksort(#[show(both)] a: &mut [i32]) {
+    if a.len() <= 1 {
+        return;
+    }
+    let pivot = a[a.len() - 1];
+    let mut i = 0;
+    for j in 0..a.len() - 1 {
+        if a[j] < pivot {
+            a.swap(i, j);
+            i += 1;
+        }
+    }
+    let last = a.len() - 1;
+    a.swap(i, last);
+    let (left, right) = a.split_at_mut(i);
+    quicksort(left);
+    quicksort(&mut right[1..]);
+}
+
+#[lg_recur]
+fn collect(#[show(len, diff)] out: &mut Vec<u32>, #[show] n: u32) {
+    if n > 0 {
+        out.push(n);
+        collect(out, n / 2);
+    }
+}
+
+fn main() {
+    let mut parent = vec![0, 0, 1, 2];
+    println!("{}", find(&mut parent, 3));
+
+    let adj = vec![vec![1, 2], vec![2], vec![0]];
+    let mut visited = vec![false; 3];
+    dfs(&mut visited, &adj, 0);
+
+    let mut a = [3, 1, 4, 1, 5];
+    quicksort(&mut a);
+    println!("{a:?}");
+
+    collect(&mut Vec::new(), 5);
+}
@@ -0,0 +1,36 @@
+use procon_lg::lg_recur;
+
+#[lg_recur(show_return, recursion_limit = 2, on_limit = truncate)]
+fn fib(#[show] n: u32) -> u32 {
+    if n <= 1 {
+        1
+    } else {
+        fib(n - 1) + fib(n - 2)
+    }
+}
+
+#[lg_recur(show_return, recursion_limit = 2, on_limit = warn)]
+fn sum(#[show] n: u32) -> u32 {
+    if n == 0 {
+        0
+    } else {
+        n + sum(n - 1)
+    }
+}
+
+#[lg_recur(show_return, recursion_limit = 2, on_limit = 0)]
+fn partitions(#[show] n: i32, #[show] k: i32) -> u64 {
+    if n == 0 {
+        return 1;
+    }
+    if n < 0 || k == 0 {
+        return 0;
+    }
+    partitions(n - k, k) + partitions(n, k - 1)
+}
+
+fn main() {
+    println!("{}", fib(4));
+    println!("{}", sum(3));
+    println!("{}", partitions(3, 3));
+}
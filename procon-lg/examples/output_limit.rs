@@ -0,0 +1,15 @@
+use procon_lg::lg_recur;
+
+#[lg_recur(show_return)]
+fn fib(#[show] n: u32) -> u32 {
+    if n <= 1 {
+        1
+    } else {
+        fib(n - 1) + fib(n - 2)
+    }
+}
+
+fn main() {
+    procon_lg::set_output_limit(Some(10));
+    println!("{}", fib(20));
+}
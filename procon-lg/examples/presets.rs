@@ -0,0 +1,33 @@
+use procon_lg::lg_recur;
+
+#[derive(Debug)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[lg_recur(show_return)]
+fn subsets(#[show(bits)] mask: u32, #[show(bin = 4)] rest: u32, #[show(len)] items: &[u32]) -> u32 {
+    if rest == 0 {
+        return items
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| mask >> i & 1 == 1)
+            .map(|(_, &x)| x)
+            .sum();
+    }
+    let bit = rest & rest.wrapping_neg();
+    subsets(mask, rest ^ bit, items).max(subsets(mask | bit, rest ^ bit, items))
+}
+
+#[lg_recur]
+fn walk(#[show(hex)] addr: usize, #[show(display)] label: &str, #[show(pretty)] p: &Point) {
+    if addr < 0x30 {
+        walk(addr + 0x10, label, &Point { x: p.y, y: p.x + 1 });
+    }
+}
+
+fn main() {
+    println!("{}", subsets(0, 0b101, &[3, 1, 4]));
+    walk(0x20, "root", &Point { x: 1, y: 2 });
+}
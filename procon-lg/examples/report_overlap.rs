@@ -0,0 +1,14 @@
+use procon_lg::lg_recur;
+
+#[lg_recur(show_return, report_overlap)]
+fn fib(#[show] n: u32) -> u32 {
+    if n <= 1 {
+        1
+    } else {
+        fib(n - 1) + fib(n - 2)
+    }
+}
+
+fn main() {
+    println!("{}", fib(5));
+}
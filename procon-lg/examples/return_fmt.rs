@@ -0,0 +1,42 @@
+use procon_lg::lg_recur;
+
+struct ModInt(u64);
+
+fn show_mod(x: &ModInt) -> String {
+    format!("{} (mod 998244353)", x.0)
+}
+
+#[lg_recur(show_return = show_mod)]
+fn power(#[show] n: u32) -> ModInt {
+    if n == 0 {
+        return ModInt(1);
+    }
+    let half = power(n / 2);
+    let sq = half.0 * half.0 % 998_244_353;
+    ModInt(if n % 2 == 1 { sq * 3 % 998_244_353 } else { sq })
+}
+
+#[lg_recur(show_return(format!("len={}, sum={}", x.len(), x.iter().sum::<u32>())))]
+fn prefix_sums(#[show] n: u32) -> Vec<u32> {
+    if n == 0 {
+        return vec![0];
+    }
+    let mut v = prefix_sums(n - 1);
+    v.push(v[v.len() - 1] + n);
+    v
+}
+
+#[lg_recur(show_return(hex))]
+fn mask_of(#[show] n: u32) -> u32 {
+    if n == 0 {
+        0
+    } else {
+        mask_of(n - 1) | 1 << (n * 4 - 1)
+    }
+}
+
+fn main() {
+    println!("{}", power(5).0);
+    println!("{:?}", prefix_sums(3));
+    println!("{}", mask_of(2));
+}
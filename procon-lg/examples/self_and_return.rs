@@ -74,6 +74,6 @@ fn main() {
     let x = 10;
     let mut y = 20;
     let mut z = 0;
-    let result = test_mutability_patterns(5, &x, &mut y, &x as *const i32, &mut z as *mut i32, 7);
+    let result = test_mutability_patterns(5, &x, &mut y, &raw const x, &raw mut z, 7);
     eprintln!("Final result: {result}, x: {x}, y: {y}, z: {z}");
 }
@@ -0,0 +1,31 @@
+use procon_lg::lg_recur;
+
+#[lg_recur(show_return)]
+fn lower_bound(a: &[i32], #[show] x: i32, #[show] l: usize, #[show] r: usize) -> usize {
+    if l == r {
+        return l;
+    }
+    #[show]
+    let mid = l + (r - l) / 2;
+    if a[mid] < x {
+        lower_bound(a, x, mid + 1, r)
+    } else {
+        lower_bound(a, x, l, mid)
+    }
+}
+
+#[lg_recur]
+fn split(#[show(bin = 4)] mask: u32) {
+    if mask.count_ones() <= 1 {
+        return;
+    }
+    #[show(bin = 4)]
+    let (low, high) = (mask & mask.wrapping_neg(), mask & (mask - 1));
+    split(high);
+    eprintln!("done with bit {}", low.trailing_zeros());
+}
+
+fn main() {
+    println!("{}", lower_bound(&[1, 3, 5, 7], 5, 0, 4));
+    split(0b1011);
+}
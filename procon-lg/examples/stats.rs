@@ -0,0 +1,31 @@
+use procon_lg::lg_recur;
+
+#[lg_recur(stats)]
+fn fib(#[show] n: u32) -> u32 {
+    if n <= 1 {
+        return 1;
+    }
+    fib(n - 1) + fib(n - 2)
+}
+
+#[lg_recur]
+fn pow(#[show] a: u64, #[show] b: u32) -> u64 {
+    if b == 0 {
+        return 1;
+    }
+    let half = pow(a, b / 2);
+    if b.is_multiple_of(2) {
+        half * half
+    } else {
+        half * half * a
+    }
+}
+
+#[lg_recur(stats)]
+fn solve(#[show] n: u32) -> u64 {
+    u64::from(fib(n)) + pow(2, n)
+}
+
+fn main() {
+    println!("{}", solve(4));
+}
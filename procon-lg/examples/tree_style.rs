@@ -0,0 +1,42 @@
+use procon_lg::{lg_recur, TreeStyle};
+
+#[lg_recur(show_return, style = ascii, report_overlap)]
+fn fib(#[show] n: u32) -> u32 {
+    if n <= 1 {
+        return 1;
+    }
+    fib(n - 1) + fib(n - 2)
+}
+
+/// Markers of the other options are ASCII too
+#[lg_recur(
+    show_return,
+    style = ascii,
+    compact,
+    call_ids,
+    max_depth = 3,
+    max_guides = 1
+)]
+fn count_paths(#[show] n: u32, #[show(diff)] calls: &mut u32) -> u32 {
+    *calls += 1;
+    if n <= 1 {
+        return 1;
+    }
+    count_paths(n - 1, calls) + count_paths(n - 2, calls)
+}
+
+#[lg_recur]
+fn countdown(#[show] n: u32) {
+    if n > 0 {
+        eprintln!("tick");
+        countdown(n - 1);
+    }
+}
+
+fn main() {
+    println!("{}", fib(3));
+    println!("{}", count_paths(4, &mut 0));
+
+    procon_lg::set_tree_style(TreeStyle::Depth);
+    countdown(2);
+}
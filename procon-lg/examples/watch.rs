@@ -0,0 +1,55 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use procon_lg::lg_recur;
+
+static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[lg_recur(show_return, watch(CALLS.load(Ordering::Relaxed)))]
+fn fib(#[show] n: u32) -> u32 {
+    CALLS.fetch_add(1, Ordering::Relaxed);
+    if n <= 1 {
+        return 1;
+    }
+    fib(n - 1) + fib(n - 2)
+}
+
+struct Stack {
+    items: Vec<u32>,
+}
+
+impl Stack {
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    #[lg_recur(watch(self.len(), self.items.last()))]
+    fn fill(&mut self, #[show] n: u32) {
+        if n == 0 {
+            return;
+        }
+        self.items.push(n);
+        self.fill(n - 1);
+    }
+}
+
+#[lg_recur(show_return, watch(dp[i][j]))]
+fn paths(dp: &mut Vec<Vec<u64>>, #[show] i: usize, #[show] j: usize) -> u64 {
+    if dp[i][j] == 0 {
+        dp[i][j] = if i == 0 || j == 0 {
+            1
+        } else {
+            paths(dp, i - 1, j) + paths(dp, i, j - 1)
+        };
+    }
+    dp[i][j]
+}
+
+fn main() {
+    println!("{}", fib(3));
+
+    let mut stack = Stack { items: vec![] };
+    stack.fill(2);
+
+    let mut dp = vec![vec![0; 2]; 2];
+    println!("{}", paths(&mut dp, 1, 1));
+}
@@ -0,0 +1,24 @@
+use procon_lg::lg_recur;
+
+#[lg_recur(show_return, when = n < 3)]
+fn fib(#[show] n: u32) -> u32 {
+    if n <= 1 {
+        return 1;
+    }
+    eprintln!("computing fib({n})");
+    fib(n - 1) + fib(n - 2)
+}
+
+#[lg_recur(show_return, when = l + 1 == r)]
+fn range_sum(a: &[i64], #[show] l: usize, #[show] r: usize) -> i64 {
+    if l + 1 == r {
+        return a[l];
+    }
+    let mid = l + (r - l) / 2;
+    range_sum(a, l, mid) + range_sum(a, mid, r)
+}
+
+fn main() {
+    println!("{}", fib(4));
+    println!("{}", range_sum(&[3, 1, 4, 1], 0, 4));
+}
@@ -0,0 +1,157 @@
+//! ANSI colors of trace output
+
+use std::borrow::Cow;
+use std::fmt::{self, Write};
+
+use crate::config;
+use crate::style::TreeStyle;
+
+/// When trace output is colored
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Never color output
+    #[default]
+    Never,
+    /// Color output if stderr is a terminal and `NO_COLOR` is not set
+    Auto,
+    /// Always color output
+    Always,
+}
+
+/// Part of a trace line with its own color
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    /// Name of the function in a call header
+    Name,
+    /// Arguments in a call header or return line
+    Args,
+    /// Return value
+    Value,
+    /// Message printed by the function
+    Message,
+}
+
+/// Colors of the guides, rotating by depth
+const GUIDE_COLORS: [&str; 6] = ["35", "34", "36", "32", "33", "31"];
+
+/// Wrap `text` in an ANSI color sequence
+fn paint_code<'a>(code: &str, text: &'a str) -> Cow<'a, str> {
+    if text.is_empty() {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(format!("\x1b[{code}m{text}\x1b[0m"))
+    }
+}
+
+/// Color `text` as `role`, if output is colored
+pub(crate) fn paint(role: Role, text: &str) -> Cow<'_, str> {
+    if !config::color() {
+        return Cow::Borrowed(text);
+    }
+    paint_code(role.code(), text)
+}
+
+impl Role {
+    /// Color code of the role
+    fn code(self) -> &'static str {
+        match self {
+            Role::Name => "1",
+            Role::Args => "36",
+            Role::Value => "32",
+            Role::Message => "33",
+        }
+    }
+}
+
+/// Displays a value colored as a role, if output is colored
+///
+/// Used by instrumented functions to color parts of a trace line while it is rendered.
+pub struct Painted<T>(pub Role, pub T);
+
+impl<T: fmt::Display> fmt::Display for Painted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !config::color() {
+            return self.1.fmt(f);
+        }
+        let mut painter = Painter {
+            out: &mut *f,
+            code: self.0.code(),
+            started: false,
+        };
+        write!(painter, "{}", self.1)?;
+        if painter.started {
+            f.write_str("\x1b[0m")?;
+        }
+        Ok(())
+    }
+}
+
+/// Writer starting the color sequence before the first text, so empty text stays uncolored
+struct Painter<'a, W> {
+    out: &'a mut W,
+    code: &'static str,
+    started: bool,
+}
+
+impl<W: Write> Write for Painter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if !self.started && !s.is_empty() {
+            self.started = true;
+            write!(self.out, "\x1b[{}m", self.code)?;
+        }
+        self.out.write_str(s)
+    }
+}
+
+/// Color a glyph at `depth` like the guide of that depth, if output is colored
+pub(crate) fn paint_glyph(depth: usize, glyph: &str) -> Cow<'_, str> {
+    if !config::color() {
+        return Cow::Borrowed(glyph);
+    }
+    paint_code(guide_color(depth), glyph)
+}
+
+/// Append the indentation of a line at `depth` to `line`, with the guides colored by depth
+///
+/// Beyond `max_guides` guides, only the outermost and the innermost ones are drawn, around a
+/// marker like `… (depth 9732) `.
+pub(crate) fn push_guides(
+    line: &mut String,
+    style: TreeStyle,
+    depth: usize,
+    max_guides: Option<usize>,
+) {
+    if style == TreeStyle::Depth {
+        if config::color() {
+            let _ = write!(line, "\x1b[{}m[{depth}] \x1b[0m", guide_color(depth));
+        } else {
+            let _ = write!(line, "[{depth}] ");
+        }
+        return;
+    }
+    let guide = style.guide();
+    match max_guides {
+        Some(max_guides) if depth > max_guides => {
+            let head = max_guides.min(1);
+            let tail = max_guides - head;
+            (0..head).for_each(|i| push_glyph(line, i, guide));
+            let _ = write!(line, "{} (depth {depth}) ", style.ellipsis());
+            (depth - tail..depth).for_each(|i| push_glyph(line, i, guide));
+        }
+        _ => (0..depth).for_each(|i| push_glyph(line, i, guide)),
+    }
+}
+
+/// Append a glyph at `depth` to `line`, colored like the guide of that depth if output is colored
+fn push_glyph(line: &mut String, depth: usize, glyph: &str) {
+    if config::color() {
+        let _ = write!(line, "\x1b[{}m{glyph}\x1b[0m", guide_color(depth));
+    } else {
+        line.push_str(glyph);
+    }
+}
+
+/// Color code of the guide at `depth`
+fn guide_color(depth: usize) -> &'static str {
+    GUIDE_COLORS[depth % GUIDE_COLORS.len()]
+}
@@ -0,0 +1,242 @@
+//! Runtime settings shared by all instrumented functions
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use std::io::IsTerminal;
+
+use crate::color::ColorChoice;
+use crate::state;
+use crate::style::TreeStyle;
+
+/// Sentinel for an unset limit
+const UNLIMITED: usize = usize::MAX;
+
+/// Maximum printed depth for all instrumented functions
+static MAX_DEPTH: AtomicUsize = AtomicUsize::new(UNLIMITED);
+
+/// Maximum number of guides drawn per line for all instrumented functions
+static MAX_GUIDES: AtomicUsize = AtomicUsize::new(UNLIMITED);
+
+/// Number of leading children shown per call for all instrumented functions
+static FIRST_CHILDREN: AtomicUsize = AtomicUsize::new(UNLIMITED);
+
+/// Number of trailing children shown per call for all instrumented functions
+static LAST_CHILDREN: AtomicUsize = AtomicUsize::new(0);
+
+/// Maximum number of trace lines written
+static MAX_LINES: AtomicUsize = AtomicUsize::new(UNLIMITED);
+
+/// Maximum number of trace bytes written
+static MAX_BYTES: AtomicUsize = AtomicUsize::new(UNLIMITED);
+
+/// Whether call statistics are printed for all instrumented functions
+static STATS: AtomicBool = AtomicBool::new(false);
+
+/// Drawing style for all instrumented functions, as the index of a `TreeStyle` variant
+static TREE_STYLE: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether trace output is colored
+static COLOR: AtomicBool = AtomicBool::new(false);
+
+/// Whether trace output is written in blocks instead of line by line
+static BUFFERED_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Whether leaf calls are printed on one line for all instrumented functions
+static COMPACT: AtomicBool = AtomicBool::new(false);
+
+/// File the folded stacks are written to
+static FOLDED_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Weight of the folded stacks, `UNLIMITED` if disabled
+static FOLDED_WEIGHT: AtomicUsize = AtomicUsize::new(UNLIMITED);
+
+/// Weight of a line of folded stack output
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FoldedWeight {
+    /// Number of calls with the stack
+    Calls,
+    /// Self time of the calls with the stack, in microseconds
+    Time,
+}
+
+fn load(setting: &AtomicUsize) -> Option<usize> {
+    match setting.load(Ordering::Relaxed) {
+        UNLIMITED => None,
+        value => Some(value),
+    }
+}
+
+/// Set the maximum printed depth for all instrumented functions
+///
+/// Calls at depth `max_depth` or deeper are still executed, but their output is replaced
+/// by a single line like `… 37 calls hidden`. `None` removes the limit.
+pub fn set_max_depth(max_depth: Option<usize>) {
+    MAX_DEPTH.store(max_depth.unwrap_or(UNLIMITED), Ordering::Relaxed);
+}
+
+pub(crate) fn max_depth() -> Option<usize> {
+    load(&MAX_DEPTH)
+}
+
+/// Set the maximum number of guides drawn per line for all instrumented functions
+///
+/// Lines deeper than `max_guides` keep the outermost guide and the innermost ones, with a marker
+/// like `│ … (depth 9732) │ │ ` in between, so that output stays linear in the number of lines.
+/// `None` removes the limit.
+pub fn set_max_guides(max_guides: Option<usize>) {
+    MAX_GUIDES.store(max_guides.unwrap_or(UNLIMITED), Ordering::Relaxed);
+}
+
+pub(crate) fn max_guides() -> Option<usize> {
+    load(&MAX_GUIDES)
+}
+
+/// Set the number of child calls shown per call for all instrumented functions
+///
+/// With `Some((first, last))`, only the first `first` and the last `last` children of each
+/// call are printed, and the others are replaced by a single line like `… 998 more calls`.
+/// `None` removes the limit.
+pub fn set_children_limit(limit: Option<(usize, usize)>) {
+    let (first, last) = limit.unwrap_or((UNLIMITED, 0));
+    FIRST_CHILDREN.store(first, Ordering::Relaxed);
+    LAST_CHILDREN.store(last, Ordering::Relaxed);
+}
+
+pub(crate) fn children_limit() -> Option<(usize, usize)> {
+    load(&FIRST_CHILDREN).map(|first| (first, LAST_CHILDREN.load(Ordering::Relaxed)))
+}
+
+/// Set the maximum number of trace lines written
+///
+/// Once the limit is reached, tracing stops with a single
+/// `[procon-lg: output truncated after N lines]` line. `None` removes the limit.
+pub fn set_output_limit(max_lines: Option<usize>) {
+    MAX_LINES.store(max_lines.unwrap_or(UNLIMITED), Ordering::Relaxed);
+}
+
+pub(crate) fn output_limit() -> Option<usize> {
+    load(&MAX_LINES)
+}
+
+/// Set the maximum number of trace bytes written
+///
+/// Once writing a line would exceed the limit, tracing stops with a single
+/// `[procon-lg: output truncated after N lines]` line. `None` removes the limit.
+pub fn set_output_byte_limit(max_bytes: Option<usize>) {
+    MAX_BYTES.store(max_bytes.unwrap_or(UNLIMITED), Ordering::Relaxed);
+}
+
+pub(crate) fn output_byte_limit() -> Option<usize> {
+    load(&MAX_BYTES)
+}
+
+/// Set whether call statistics are printed for all instrumented functions
+///
+/// When enabled, a table of calls, maximum depth, early returns and leaf calls per function is
+/// printed whenever the outermost instrumented call returns.
+pub fn set_stats(enabled: bool) {
+    STATS.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn stats() -> bool {
+    STATS.load(Ordering::Relaxed)
+}
+
+/// Set whether trace output is written in blocks instead of line by line
+///
+/// By default every trace line is flushed to stderr as it is printed, so that it stays in order
+/// with other output and survives an abort. When enabled, trace output is flushed when the
+/// outermost instrumented call returns, and before a warning, call stack or panic, which is much
+/// faster for large traces. Output up to a stack overflow or abort may then be lost.
+pub fn set_buffered_output(enabled: bool) {
+    if enabled {
+        state::flush_on_panic();
+    }
+    BUFFERED_OUTPUT.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn buffered_output() -> bool {
+    BUFFERED_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Set whether leaf calls of all instrumented functions are printed on one line
+///
+/// When enabled, a call without instrumented children and messages is printed as its header
+/// and its return line merged, e.g. `fib(n:1) → 1`.
+pub fn set_compact(enabled: bool) {
+    COMPACT.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn compact() -> bool {
+    COMPACT.load(Ordering::Relaxed)
+}
+
+/// Set the drawing style of the call tree for all instrumented functions
+///
+/// Functions with a `style` option keep their own style.
+pub fn set_tree_style(style: TreeStyle) {
+    TREE_STYLE.store(style as usize, Ordering::Relaxed);
+}
+
+pub(crate) fn tree_style() -> TreeStyle {
+    match TREE_STYLE.load(Ordering::Relaxed) {
+        1 => TreeStyle::Ascii,
+        2 => TreeStyle::Depth,
+        _ => TreeStyle::Unicode,
+    }
+}
+
+/// Set when trace output is colored
+///
+/// Colored output rotates the colors of the guides by depth, and colors function names,
+/// arguments, return values and messages. With `ColorChoice::Auto`, output is colored if stderr
+/// is a terminal and the `NO_COLOR` environment variable is not set when this is called.
+pub fn set_color(choice: ColorChoice) {
+    let enabled = match choice {
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+        }
+        ColorChoice::Always => true,
+    };
+    COLOR.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn color() -> bool {
+    COLOR.load(Ordering::Relaxed)
+}
+
+/// Set the file folded stacks of all instrumented calls are written to
+///
+/// Each line is a stack of instrumented function names, outermost first, and a weight, e.g.
+/// `solve;dfs;dfs 42`, as read by `inferno` or `flamegraph.pl`. Stacks are aggregated over the
+/// whole run of a thread, and the file is written when the thread exits, or by
+/// [`write_folded`](crate::write_folded). `None` disables the output.
+pub fn set_folded_output(path: Option<PathBuf>, weight: FoldedWeight) {
+    let enabled = path.is_some();
+    *FOLDED_PATH
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = path;
+    let weight = match weight {
+        _ if !enabled => UNLIMITED,
+        FoldedWeight::Calls => 0,
+        FoldedWeight::Time => 1,
+    };
+    FOLDED_WEIGHT.store(weight, Ordering::Relaxed);
+}
+
+pub(crate) fn folded_weight() -> Option<FoldedWeight> {
+    match load(&FOLDED_WEIGHT)? {
+        0 => Some(FoldedWeight::Calls),
+        _ => Some(FoldedWeight::Time),
+    }
+}
+
+pub(crate) fn folded_path() -> Option<PathBuf> {
+    FOLDED_PATH
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .clone()
+}
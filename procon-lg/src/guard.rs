@@ -0,0 +1,382 @@
+//! Per-call bookkeeping of instrumented functions
+
+use std::cell::Cell;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::config::{self, FoldedWeight};
+use crate::state::{self, Frame, LG_STATE};
+use crate::style::TreeStyle;
+
+/// Options of a single call of an instrumented function
+// Flag options of the macro map to bools
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Copy, Debug)]
+pub struct CallOptions {
+    /// Name of the function
+    pub name: &'static str,
+    /// Whether output of the call is shown, e.g. the result of a `when` predicate
+    pub visible: bool,
+    /// Result of the `focus` predicate, if the function has one
+    pub focus: Option<bool>,
+    /// Maximum printed depth of the function
+    pub max_depth: Option<usize>,
+    /// Numbers of leading and trailing children shown per call of the function
+    pub children_limit: Option<(usize, usize)>,
+    /// Whether the call is counted in the overlapping subproblem report
+    pub report_overlap: bool,
+    /// Whether call statistics are printed when the outermost call returns
+    pub stats: bool,
+    /// Whether the wall time of the call is measured
+    pub time: bool,
+    /// Whether the call is printed on one line if it is a leaf
+    pub compact: bool,
+    /// Drawing style of the function, overriding the global one
+    pub style: Option<TreeStyle>,
+    /// Maximum number of guides drawn per line of the function
+    pub max_guides: Option<usize>,
+}
+
+impl Default for CallOptions {
+    fn default() -> Self {
+        CallOptions {
+            name: "",
+            visible: true,
+            focus: None,
+            max_depth: None,
+            children_limit: None,
+            report_overlap: false,
+            stats: false,
+            time: false,
+            compact: false,
+            style: None,
+            max_guides: None,
+        }
+    }
+}
+
+/// RAII guard for managing recursion depth
+/// Automatically increments depth on creation and decrements on drop
+pub struct DepthGuard {
+    depth: usize,
+    display_depth: usize,
+    visible: Cell<bool>,
+    focus_root: bool,
+    early_return: Cell<bool>,
+}
+
+impl DepthGuard {
+    /// Create a new depth guard, incrementing the current depth
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new depth guard for a call with the given options
+    ///
+    /// Hidden calls still count towards the depth of nested calls.
+    ///
+    /// Once a function with a focus predicate is called, output is suppressed except for the
+    /// subtrees of calls for which the predicate holds, until the outermost call returns. Those
+    /// are printed with depth rebased to zero.
+    pub fn with_options(options: CallOptions) -> Self {
+        LG_STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            let depth = state.depth();
+            if depth == 0 {
+                state.calls = 0;
+                state.started = Some(Instant::now());
+            }
+            state.calls += 1;
+            state.stats_enabled |= options.stats || config::stats();
+
+            let mut focus_root = false;
+            if let Some(focus) = options.focus {
+                state.focus_mode = true;
+                if focus && state.focus_depth.is_none() {
+                    state.focus_depth = Some(depth);
+                    focus_root = true;
+                }
+            }
+
+            let in_focus = !state.focus_mode || state.focus_depth.is_some();
+            let display_depth = depth - state.focus_depth.unwrap_or(0);
+            let mut visible = options.visible && in_focus && !state.truncated;
+
+            let max_depth = options
+                .max_depth
+                .unwrap_or(usize::MAX)
+                .min(config::max_depth().unwrap_or(usize::MAX));
+            if visible && display_depth >= max_depth {
+                state.hide_call(display_depth);
+                visible = false;
+            }
+
+            state.flush_pending_header();
+            let mut capturing = false;
+            if let Some(parent) = state.frames.last_mut() {
+                parent.children += 1;
+                // Hidden children print nothing of their own, so they are not capped
+                if let Some((first, _)) =
+                    parent.children_limit.filter(|_| visible && parent.visible)
+                {
+                    parent.shown_children += 1;
+                    if parent.shown_children > first {
+                        parent.children_depth = display_depth;
+                        parent.capturing = true;
+                        capturing = true;
+                        parent.tail.push_back(Vec::new());
+                    }
+                }
+            }
+            if capturing {
+                state.capturing_frames.push(depth - 1);
+            }
+
+            let first_call = state.calls;
+            state.last_id += 1;
+            let id = state.last_id;
+            // A call site left by a call which is not instrumented is dropped here
+            let call_site = state
+                .call_site
+                .take()
+                .filter(|&(name, caller_depth, _)| name == options.name && caller_depth == depth)
+                .map(|(.., line)| line);
+            state.frames.push(Frame {
+                name: options.name,
+                visible,
+                children_limit: options.children_limit.or_else(config::children_limit),
+                report_overlap: options.report_overlap,
+                first_call,
+                id,
+                call_site,
+                compact: options.compact || config::compact(),
+                style: options.style.unwrap_or_else(config::tree_style),
+                max_guides: options.max_guides.or_else(config::max_guides),
+                show_time: options.time,
+                started: (options.time || config::folded_weight() == Some(FoldedWeight::Time))
+                    .then(Instant::now),
+                ..Frame::default()
+            });
+
+            DepthGuard {
+                depth,
+                display_depth,
+                visible: Cell::new(visible),
+                focus_root,
+                early_return: Cell::new(false),
+            }
+        })
+    }
+
+    /// Get the current depth level
+    pub fn current_depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Check if output of the current call is shown
+    pub fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
+    /// Hide output of the current call, e.g. beyond a limit with `on_limit = truncate`
+    ///
+    /// The call is counted in the `… N calls hidden` summary line.
+    pub fn hide(&self) {
+        if self.visible.replace(false) {
+            LG_STATE.with(|state| {
+                let mut state = state.borrow_mut();
+                state.frames[self.depth].visible = false;
+                if let Some(parent) = self.depth.checked_sub(1) {
+                    state.uncount_child(parent);
+                }
+                state.hide_call(self.display_depth);
+            });
+        }
+    }
+
+    /// Record that the current call is left with `return`, for the call statistics
+    pub fn mark_early_return(&self) {
+        self.early_return.set(true);
+    }
+
+    /// Print the header of the current call
+    ///
+    /// With `compact`, a single-line header is held back until the call prints anything else,
+    /// and merged with the return line if the call is a leaf.
+    pub fn print_header(&self, header: fmt::Arguments) {
+        state::with_rendered(header, |header| {
+            LG_STATE.with(|state| state.borrow_mut().print_header(self.display_depth, header));
+        });
+    }
+
+    /// Print the return line of the current call, e.g. `└ return: 3` for `return: 3`
+    ///
+    /// Without text, the line is only the glyph ending the call.
+    pub fn print_exit(&self, text: fmt::Arguments) {
+        state::with_rendered(text, |text| {
+            LG_STATE.with(|state| state.borrow_mut().print_exit(self.display_depth, text));
+        });
+    }
+
+    /// Print a message of the current call, indented one level deeper than its header
+    ///
+    /// Callers check that the current call is visible, so that the message is only rendered if
+    /// it is shown.
+    pub fn print_message(&self, message: fmt::Arguments) {
+        crate::print_message(self.display_depth + 1, message);
+    }
+
+    /// Arrow between the rendered values of an argument on entry and on exit, e.g. `→`
+    pub fn arrow(&self) -> &'static str {
+        LG_STATE.with(|state| state.borrow().frames[self.depth].style.arrow())
+    }
+
+    /// Take an empty buffer to render the arguments of the current call into
+    ///
+    /// The buffer is reused from a finished call, if any, and handed back with `set_args`.
+    pub fn args_buffer(&self) -> String {
+        LG_STATE.with(|state| state.borrow_mut().take_args_buffer())
+    }
+
+    /// Record the rendered arguments of the current call, shown in the call stack
+    pub fn set_args(&self, args: String) {
+        LG_STATE.with(|state| state.borrow_mut().frames[self.depth].args = Some(args));
+    }
+
+    /// Warn if an active call of the same function has the same arguments as the current call
+    ///
+    /// The warning shows the cycle, from that ancestor to the current call, and is printed once
+    /// per outermost call.
+    pub fn check_cycle(&self) {
+        LG_STATE.with(|state| state.borrow_mut().check_cycle(self.depth));
+    }
+
+    /// Stop the clock of the current call and display its inclusive and self time, e.g.
+    /// ` (time: 1.20ms, self: 310.00µs)`
+    pub fn time_suffix(&self) -> impl fmt::Display {
+        LG_STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            let frame = &mut state.frames[self.depth];
+            let style = frame.style;
+            TimeSuffix(frame.stop_clock().filter(|_| frame.show_time), style)
+        })
+    }
+
+    /// Display the id of the current call and of its parent, e.g. ` #17←#12`
+    ///
+    /// Nothing is displayed on a return line merged into a compact line, whose header already
+    /// ends with the ids.
+    pub fn call_id_suffix(&self) -> impl fmt::Display {
+        LG_STATE.with(|state| {
+            let state = state.borrow();
+            let frame = &state.frames[self.depth];
+            let parent = self.depth.checked_sub(1).map(|i| state.frames[i].id);
+            let ids = frame.pending_header.is_none().then_some((frame.id, parent));
+            CallIdSuffix(ids, frame.style)
+        })
+    }
+
+    /// Display the source line the current call was made from, e.g. `  @L8`
+    pub fn call_site_suffix(&self) -> impl fmt::Display {
+        LG_STATE.with(|state| CallSiteSuffix(state.borrow().frames[self.depth].call_site))
+    }
+
+    /// Get the number of calls since the outermost call started, including the current one
+    pub fn call_count(&self) -> usize {
+        LG_STATE.with(|state| state.borrow().calls)
+    }
+
+    /// Get the time elapsed since the outermost call started
+    pub fn elapsed(&self) -> Duration {
+        LG_STATE.with(|state| {
+            state
+                .borrow()
+                .started
+                .map_or(Duration::ZERO, |s| s.elapsed())
+        })
+    }
+}
+
+/// Inclusive and self time of a call, if shown
+struct TimeSuffix(Option<(Duration, Duration)>, TreeStyle);
+
+impl fmt::Display for TimeSuffix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some((time, self_time)) => write!(
+                f,
+                " (time: {}, self: {})",
+                self.1.duration(time),
+                self.1.duration(self_time)
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Id of a call and of its parent, if any, unless hidden
+struct CallIdSuffix(Option<(usize, Option<usize>)>, TreeStyle);
+
+impl fmt::Display for CallIdSuffix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some((id, Some(parent))) => write!(f, " #{id}{}#{parent}", self.1.back_arrow()),
+            Some((id, None)) => write!(f, " #{id}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Source line a call was made from, if known
+struct CallSiteSuffix(Option<usize>);
+
+impl fmt::Display for CallSiteSuffix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(line) => write!(f, "  @L{line}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for DepthGuard {
+    fn default() -> Self {
+        Self::with_options(CallOptions::default())
+    }
+}
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        LG_STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            state.flush_pending_header();
+            state.flush_children();
+            state.leave_cycle_check();
+            state.record_folded();
+            state.record_time();
+            state.record_overlap();
+            state.record_stats(self.early_return.get());
+            // Summaries are printed in the drawing style of the outermost call, before it is popped
+            if state.depth() == 1 {
+                state.flush_hidden_calls();
+                state.flush_stats();
+                state.flush_slowest_calls();
+                state.flush_overlaps();
+            }
+            if let Some(frame) = state.frames.pop() {
+                state.recycle_args(frame.args);
+            }
+            if let Some(parent) = state.depth().checked_sub(1) {
+                state.end_capture(parent);
+            }
+            if self.focus_root {
+                state.focus_depth = None;
+            }
+            if state.depth() == 0 {
+                state.focus_mode = false;
+                state.cycle_reported = false;
+                state.flush_output();
+            }
+        });
+    }
+}
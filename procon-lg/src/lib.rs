@@ -2,49 +2,124 @@
 //!
 //! A procedural macro library for debugging recursive functions in competitive programming
 
-use std::cell::RefCell;
+mod color;
+mod config;
+mod guard;
+mod state;
+mod style;
 
-// Thread-local depth counter for global depth management
-thread_local! {
-    static LG_DEPTH: RefCell<usize> = const { RefCell::new(0) };
+use std::fmt;
+
+pub use color::{ColorChoice, Painted, Role};
+pub use config::{
+    set_buffered_output, set_children_limit, set_color, set_compact, set_folded_output,
+    set_max_depth, set_max_guides, set_output_byte_limit, set_output_limit, set_stats,
+    set_tree_style, FoldedWeight,
+};
+pub use guard::{CallOptions, DepthGuard};
+pub use style::TreeStyle;
+
+/// Print a line of trace output, indented to `depth`
+///
+/// Used for messages of instrumented functions, which are colored if output is colored.
+fn print_line(depth: usize, line: &str) {
+    let line = color::paint(Role::Message, line);
+    state::LG_STATE.with(|state| state.borrow_mut().print_line(depth, &line));
 }
 
-/// RAII guard for managing recursion depth
-/// Automatically increments depth on creation and decrements on drop
-pub struct DepthGuard {
-    depth: usize,
+/// Print a message of an instrumented function, indented to `depth`, one trace line per line
+///
+/// An empty message or a trailing newline prints an empty line.
+pub(crate) fn print_message(depth: usize, message: fmt::Arguments) {
+    state::with_rendered(message, |message| {
+        for line in message.lines() {
+            print_line(depth, line);
+        }
+        if message.is_empty() || message.ends_with('\n') {
+            print_line(depth, "");
+        }
+    });
 }
 
-impl DepthGuard {
-    /// Create a new depth guard, incrementing the current depth
-    pub fn new() -> Self {
-        Self::default()
-    }
+/// Record the source line of the call of `name` about to be made, and pass `value` through
+///
+/// Used by `call_site` to wrap the last argument of recursive calls. The line is only shown if
+/// the next instrumented call is of `name` and made from the current depth.
+pub fn at_call_site<T>(name: &'static str, line: usize, value: T) -> T {
+    state::LG_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let depth = state.depth();
+        state.call_site = Some((name, depth, line));
+    });
+    value
+}
 
-    /// Get the current depth level
-    pub fn current_depth(&self) -> usize {
-        self.depth
-    }
+/// Print a warning of the tracer, e.g. for a limit exceeded with `on_limit = warn`
+///
+/// Warnings are written to stderr immediately and do not count towards the output limit.
+pub fn print_warning(message: &str) {
+    state::LG_STATE.with(|state| state.borrow_mut().warn(message));
 }
 
-impl Default for DepthGuard {
-    fn default() -> Self {
-        LG_DEPTH.with(|depth| {
-            let mut d = depth.borrow_mut();
-            *d += 1;
-            DepthGuard { depth: *d - 1 }
-        })
-    }
+/// Write out the trace output buffered so far
+///
+/// With buffered output, the trace is flushed when the outermost instrumented call returns, so
+/// call this before leaving the program from within a call, e.g. with `std::process::exit`.
+pub fn flush_output() {
+    state::LG_STATE.with(|state| state.borrow_mut().flush_output());
 }
 
-impl Drop for DepthGuard {
-    fn drop(&mut self) {
-        LG_DEPTH.with(|depth| {
-            let mut d = depth.borrow_mut();
-            *d -= 1;
-        });
+/// Write the folded stacks of the current thread so far to the file set by `set_folded_output`
+///
+/// The file is written when the thread exits, so call this to read it earlier, or before leaving
+/// the program with e.g. `std::process::exit`, which skips the write.
+pub fn write_folded() {
+    state::LG_STATE.with(|state| state.borrow_mut().write_folded());
+}
+
+/// Render the active instrumented calls of the current thread, outermost first
+///
+/// Arguments are included for calls whose header was rendered.
+pub fn call_stack() -> String {
+    state::LG_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.flush_output();
+        state.call_stack()
+    })
+}
+
+/// Displays the indices of the set bits of an integer, e.g. `{0, 2, 5}`
+///
+/// Used by `#[show(bits)]`, and handy in custom formatters for bitmask DP.
+pub struct Bits<'a, T: ?Sized>(pub &'a T);
+
+impl<T: fmt::Binary + ?Sized> fmt::Display for Bits<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let binary = format!("{:b}", self.0);
+        f.write_str("{")?;
+        let set_bits = binary.bytes().rev().enumerate().filter(|&(_, b)| b == b'1');
+        for (k, (i, _)) in set_bits.enumerate() {
+            if k > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{i}")?;
+        }
+        f.write_str("}")
     }
 }
 
 // Re-export the procedural macro
 pub use procon_lg_macros::lg_recur;
+
+/// Print a message of the current call of an instrumented function, in place of `eprintln!`
+///
+/// The arguments are only evaluated if output of the call is shown.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __lg_print {
+    ($guard:ident, $($args:tt)*) => {
+        if $guard.is_visible() {
+            $guard.print_message(::core::format_args!($($args)*));
+        }
+    };
+}
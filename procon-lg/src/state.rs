@@ -0,0 +1,853 @@
+//! Tracing state shared by all instrumented functions on a thread
+
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt::{self, Write};
+use std::hash::{BuildHasher, BuildHasherDefault};
+use std::io::{self, BufWriter, Stderr, Write as _};
+use std::panic;
+use std::sync::Once;
+use std::time::{Duration, Instant};
+
+use crate::color;
+use crate::config::{self, FoldedWeight};
+use crate::style::TreeStyle;
+
+/// State of an active instrumented call
+// Flag options of the call map to bools
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Default)]
+pub(crate) struct Frame {
+    /// Name of the function
+    pub name: &'static str,
+    /// Rendered arguments, if the call header was rendered
+    pub args: Option<String>,
+    /// Maximum numbers of leading and trailing children shown
+    pub children_limit: Option<(usize, usize)>,
+    /// Whether output of the call is shown
+    pub visible: bool,
+    /// Number of child calls so far
+    pub children: usize,
+    /// Number of shown child calls so far, counted towards `children_limit`
+    pub shown_children: usize,
+    /// Whether output of the current child goes into `tail`
+    pub capturing: bool,
+    /// Output of the most recent children beyond the leading ones, oldest first
+    pub tail: VecDeque<Vec<String>>,
+    /// Number of children dropped from `tail`
+    pub elided_children: usize,
+    /// Display depth of the children
+    pub children_depth: usize,
+    /// Hash of the name and arguments of the call, counted in `State::active_args` with
+    /// `detect_cycle`
+    pub args_hash: Option<u64>,
+    /// Whether the call is counted in the overlapping subproblem report
+    pub report_overlap: bool,
+    /// Value of `State::calls` when the call started
+    pub first_call: usize,
+    /// Whether the time of the call is shown, with the `time` option
+    pub show_time: bool,
+    /// Start time of the call, if its wall time is measured
+    pub started: Option<Instant>,
+    /// Wall time of the call, fixed by its first measurement on exit
+    pub time: Option<Duration>,
+    /// Wall time spent in the instrumented children so far
+    pub children_time: Duration,
+    /// Id of the call, numbered from 1 in the order of calls on the thread
+    pub id: usize,
+    /// Source line of the call expression, for recursive calls with the `call_site` option
+    pub call_site: Option<usize>,
+    /// Whether the call is printed on one line if it is a leaf
+    pub compact: bool,
+    /// Display depth and header of the call, held back until it prints anything else
+    pub pending_header: Option<(usize, String)>,
+    /// Drawing style of the call
+    pub style: TreeStyle,
+    /// Maximum number of guides drawn for lines of the call
+    pub max_guides: Option<usize>,
+}
+
+impl Frame {
+    /// Stop the clock of the call and get its inclusive and self time, if its wall time is measured
+    ///
+    /// Only the first measurement counts, so that the return line, the parent and the summaries
+    /// agree on the time of the call.
+    pub fn stop_clock(&mut self) -> Option<(Duration, Duration)> {
+        let started = self.started?;
+        let time = *self.time.get_or_insert_with(|| started.elapsed());
+        Some((time, time.saturating_sub(self.children_time)))
+    }
+}
+
+/// A measured call, for the summary of the slowest calls
+pub(crate) struct TimedCall {
+    /// Rendered call, e.g. `fib(n:3)`
+    pub call: String,
+    /// Inclusive time
+    pub time: Duration,
+    /// Time outside the instrumented children
+    pub self_time: Duration,
+}
+
+/// Repeated evaluations of an argument tuple, for the overlapping subproblem report
+#[derive(Default)]
+pub(crate) struct Overlap {
+    /// Number of evaluations
+    pub count: usize,
+    /// Number of calls in the subtrees of all evaluations but the first
+    pub wasted_calls: usize,
+}
+
+/// Call statistics of a function, for the summary table
+#[derive(Default)]
+pub(crate) struct CallStats {
+    /// Number of calls
+    pub calls: usize,
+    /// Maximum depth of a call
+    pub max_depth: usize,
+    /// Number of calls left with `return`
+    pub early_returns: usize,
+    /// Number of calls without instrumented children
+    pub leaf_calls: usize,
+}
+
+/// Tracing state shared by all instrumented functions on a thread
+// Independent flags of the tracer map to bools
+#[allow(clippy::struct_excessive_bools)]
+pub(crate) struct State {
+    /// Active instrumented calls, innermost last
+    pub frames: Vec<Frame>,
+    /// Indices of the frames whose output of the current child goes into `tail`, innermost last
+    pub capturing_frames: Vec<usize>,
+    /// Whether some function has a `focus` predicate, which suppresses output outside focus
+    pub focus_mode: bool,
+    /// Depth of the call whose subtree is being printed in focus mode
+    pub focus_depth: Option<usize>,
+    /// Number of calls hidden by a depth limit since the last printed line
+    pub hidden_calls: usize,
+    /// Display depth of the outermost of the hidden calls
+    pub hidden_depth: usize,
+    /// Number of trace lines written to stderr
+    pub lines_written: usize,
+    /// Number of trace bytes written to stderr
+    pub bytes_written: usize,
+    /// Whether the output limit has been reached
+    pub truncated: bool,
+    /// Number of calls since the outermost call started
+    pub calls: usize,
+    /// Start time of the outermost call
+    pub started: Option<Instant>,
+    /// Number of active calls by hash of function and arguments, for functions with `detect_cycle`
+    pub active_args: HashMap<u64, usize, BuildHasherDefault<DefaultHasher>>,
+    /// Whether a cycle has been reported since the outermost call started
+    pub cycle_reported: bool,
+    /// Evaluations of argument tuples since the outermost call started, by function and arguments
+    pub overlaps: BTreeMap<(&'static str, String), Overlap>,
+    /// Whether call statistics are collected since the outermost call started
+    pub stats_enabled: bool,
+    /// Call statistics by function
+    pub stats: BTreeMap<&'static str, CallStats>,
+    /// Measured calls with the largest self time since the outermost call started, slowest first
+    pub slowest_calls: Vec<TimedCall>,
+    /// Weights of the folded stacks of the whole run
+    pub folded: BTreeMap<String, u128>,
+    /// Whether the folded stacks changed since they were last written
+    pub folded_changed: bool,
+    /// Id of the most recent call
+    pub last_id: usize,
+    /// Function name, depth and source line of the call about to be made, taken by the next call
+    pub call_site: Option<(&'static str, usize, usize)>,
+    /// Buffer the line being written is rendered into, reused across lines
+    pub line: String,
+    /// Rendered arguments of finished calls, reused for the arguments of later calls
+    pub spare_args: Vec<String>,
+    /// Writer of trace output to stderr, created on the first line
+    pub out: Option<BufWriter<Stderr>>,
+}
+
+// Thread-local state for global depth management
+thread_local! {
+    pub(crate) static LG_STATE: RefCell<State> = const {
+        RefCell::new(State {
+            frames: Vec::new(),
+            capturing_frames: Vec::new(),
+            focus_mode: false,
+            focus_depth: None,
+            hidden_calls: 0,
+            hidden_depth: 0,
+            lines_written: 0,
+            bytes_written: 0,
+            truncated: false,
+            calls: 0,
+            started: None,
+            active_args: HashMap::with_hasher(BuildHasherDefault::new()),
+            cycle_reported: false,
+            overlaps: BTreeMap::new(),
+            stats_enabled: false,
+            stats: BTreeMap::new(),
+            slowest_calls: Vec::new(),
+            folded: BTreeMap::new(),
+            folded_changed: false,
+            last_id: 0,
+            call_site: None,
+            line: String::new(),
+            spare_args: Vec::new(),
+            out: None,
+        })
+    };
+}
+
+thread_local! {
+    /// Buffer messages are rendered into before they are printed, reused across messages
+    static RENDERED: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// Install a panic hook, once, which writes out the buffered trace output of the panicking thread
+/// before the previous hook prints the panic message
+pub(crate) fn flush_on_panic() {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            // The state is borrowed if the tracer itself panicked, or gone if the thread is exiting
+            let _ = LG_STATE.try_with(|state| {
+                if let Ok(mut state) = state.try_borrow_mut() {
+                    state.flush_output();
+                }
+            });
+            previous(info);
+        }));
+    });
+}
+
+/// Render `text` into a reused buffer and pass the result to `f`
+///
+/// Rendering may run user code which prints trace output itself, so a nested message is rendered
+/// into a buffer of its own.
+pub(crate) fn with_rendered(text: fmt::Arguments, f: impl FnOnce(&str)) {
+    RENDERED.with(|buffer| match buffer.try_borrow_mut() {
+        Ok(mut buffer) => {
+            buffer.clear();
+            let _ = buffer.write_fmt(text);
+            f(&buffer);
+        }
+        Err(_) => f(&text.to_string()),
+    });
+}
+
+// Folded stacks are aggregated over the whole run, and written once when the thread exits
+impl Drop for State {
+    fn drop(&mut self) {
+        self.write_folded();
+        self.flush_output();
+    }
+}
+
+impl State {
+    /// Number of active instrumented calls
+    pub fn depth(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Render the active calls, outermost first
+    pub fn call_stack(&self) -> String {
+        /// Number of frames shown at each end of a long stack
+        const SHOWN: usize = 10;
+
+        let mut stack = format!("call stack ({} calls, outermost first):", self.depth());
+        for (i, frame) in self.frames.iter().enumerate() {
+            if i == SHOWN && self.depth() > 2 * SHOWN {
+                let ellipsis = self.style().ellipsis();
+                let omitted = self.depth() - 2 * SHOWN;
+                let _ = write!(stack, "\n  {ellipsis} {omitted} calls {ellipsis}");
+            }
+            if i >= SHOWN && i + SHOWN < self.depth() {
+                continue;
+            }
+            let _ = write!(stack, "\n  {}", frame.name);
+            if let Some(args) = &frame.args {
+                let _ = write!(stack, "({args})");
+            }
+        }
+        stack
+    }
+
+    /// Count the arguments of the call at `depth` as active, and warn the first time since the
+    /// outermost call started that an active call of the same function has the same arguments
+    pub fn check_cycle(&mut self, depth: usize) {
+        let frame = &mut self.frames[depth];
+        let hash = self
+            .active_args
+            .hasher()
+            .hash_one((frame.name, frame.args.as_deref().unwrap_or_default()));
+        frame.args_hash = Some(hash);
+        let count = self.active_args.entry(hash).or_default();
+        *count += 1;
+        if *count == 1 || self.cycle_reported {
+            return;
+        }
+        // Equal hashes of different arguments are no cycle
+        let Some(cycle) = self.find_cycle(depth) else {
+            return;
+        };
+        self.cycle_reported = true;
+        self.warn(&format!(
+            "cycle detected, a call was re-entered with the same arguments: {cycle}"
+        ));
+    }
+
+    /// Stop counting the arguments of the innermost call as active
+    pub fn leave_cycle_check(&mut self) {
+        let Some(frame) = self.frames.last_mut() else {
+            return;
+        };
+        let Some(hash) = frame.args_hash.take() else {
+            return;
+        };
+        if let Some(count) = self.active_args.get_mut(&hash) {
+            *count -= 1;
+            if *count == 0 {
+                self.active_args.remove(&hash);
+            }
+        }
+    }
+
+    /// Render the path from the innermost ancestor of the call at `depth` with the same name
+    /// and arguments to that call, e.g. `dfs(v:1) -> dfs(v:3) -> dfs(v:1)`, if there is one
+    fn find_cycle(&self, depth: usize) -> Option<String> {
+        let frame = &self.frames[depth];
+        let start = self.frames[..depth]
+            .iter()
+            .rposition(|ancestor| ancestor.name == frame.name && ancestor.args == frame.args)?;
+        let mut cycle = String::new();
+        for (i, frame) in self.frames[start..=depth].iter().enumerate() {
+            if i > 0 {
+                cycle.push_str(" -> ");
+            }
+            let _ = write!(
+                cycle,
+                "{}({})",
+                frame.name,
+                frame.args.as_deref().unwrap_or_default()
+            );
+        }
+        Some(cycle)
+    }
+
+    /// Record the evaluation of the innermost call for the overlapping subproblem report
+    pub fn record_overlap(&mut self) {
+        let Some(frame) = self.frames.last_mut() else {
+            return;
+        };
+        if !frame.report_overlap {
+            return;
+        }
+        let Some(args) = frame.args.take() else {
+            return;
+        };
+        let subtree_calls = self.calls - frame.first_call + 1;
+        let overlap = self.overlaps.entry((frame.name, args)).or_default();
+        if overlap.count > 0 {
+            overlap.wasted_calls += subtree_calls;
+        }
+        overlap.count += 1;
+    }
+
+    /// Add the wall time of the innermost call to its parent, and record it if it is measured
+    pub fn record_time(&mut self) {
+        /// Number of the slowest calls kept
+        const KEPT: usize = 10;
+
+        let Some(frame) = self.frames.last_mut() else {
+            return;
+        };
+        let Some((time, self_time)) = frame.stop_clock() else {
+            return;
+        };
+        let call = frame.show_time.then(|| match &frame.args {
+            Some(args) => format!("{}({args})", frame.name),
+            None => frame.name.to_string(),
+        });
+        if let [.., parent, _] = self.frames.as_mut_slice() {
+            parent.children_time += time;
+        }
+        // Calls are measured without the `time` option only for the folded stacks
+        let Some(call) = call else {
+            return;
+        };
+        let position = self
+            .slowest_calls
+            .partition_point(|slower| slower.self_time >= self_time);
+        if position < KEPT {
+            self.slowest_calls.insert(
+                position,
+                TimedCall {
+                    call,
+                    time,
+                    self_time,
+                },
+            );
+            self.slowest_calls.truncate(KEPT);
+        }
+    }
+
+    /// Add the innermost call to the folded stacks, if enabled
+    pub fn record_folded(&mut self) {
+        let Some(weight) = config::folded_weight() else {
+            return;
+        };
+        let Some(frame) = self.frames.last_mut() else {
+            return;
+        };
+        let weight = match weight {
+            FoldedWeight::Calls => 1,
+            FoldedWeight::Time => frame
+                .stop_clock()
+                .map_or(0, |(_, self_time)| self_time.as_micros()),
+        };
+        let names: Vec<&str> = self.frames.iter().map(|frame| frame.name).collect();
+        *self.folded.entry(names.join(";")).or_default() += weight;
+        self.folded_changed = true;
+    }
+
+    /// Rewrite the folded stack output file with the stacks so far, if enabled and changed
+    pub fn write_folded(&mut self) {
+        if !std::mem::take(&mut self.folded_changed) {
+            return;
+        }
+        let Some(path) = config::folded_path() else {
+            return;
+        };
+        let mut folded = String::new();
+        for (stack, weight) in &self.folded {
+            let _ = writeln!(folded, "{stack} {weight}");
+        }
+        if let Err(err) = std::fs::write(&path, folded) {
+            self.warn(&format!(
+                "failed to write folded stacks to {}: {err}",
+                path.display()
+            ));
+        }
+    }
+
+    /// Print the summary of the measured calls with the largest self time
+    pub fn flush_slowest_calls(&mut self) {
+        let slowest_calls = std::mem::take(&mut self.slowest_calls);
+        if slowest_calls.is_empty() {
+            return;
+        }
+        let style = self.style();
+        self.emit("slowest calls by self time:");
+        for TimedCall {
+            call,
+            time,
+            self_time,
+        } in slowest_calls
+        {
+            self.emit(&format!(
+                "  {call}  time: {}, self: {}",
+                style.duration(time),
+                style.duration(self_time)
+            ));
+        }
+    }
+
+    /// Record the innermost call in the call statistics, if enabled
+    pub fn record_stats(&mut self, early_return: bool) {
+        if !self.stats_enabled {
+            return;
+        }
+        let depth = self.depth() - 1;
+        let Some(frame) = self.frames.last() else {
+            return;
+        };
+        let stats = self.stats.entry(frame.name).or_default();
+        stats.calls += 1;
+        stats.max_depth = stats.max_depth.max(depth);
+        stats.early_returns += usize::from(early_return);
+        stats.leaf_calls += usize::from(frame.children == 0);
+    }
+
+    /// Print the table of call statistics by function
+    pub fn flush_stats(&mut self) {
+        self.stats_enabled = false;
+        let stats = std::mem::take(&mut self.stats);
+        if stats.is_empty() {
+            return;
+        }
+        let header = [
+            "function",
+            "calls",
+            "max depth",
+            "early returns",
+            "leaf calls",
+        ];
+        let rows: Vec<[String; 5]> = stats
+            .into_iter()
+            .map(|(name, stats)| {
+                [
+                    name.to_string(),
+                    stats.calls.to_string(),
+                    stats.max_depth.to_string(),
+                    stats.early_returns.to_string(),
+                    stats.leaf_calls.to_string(),
+                ]
+            })
+            .collect();
+        let widths: Vec<usize> = (0..header.len())
+            .map(|i| {
+                rows.iter()
+                    .map(|row| row[i].chars().count())
+                    .chain([header[i].len()])
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        self.emit("call statistics:");
+        let header = header.map(str::to_string);
+        for row in std::iter::once(&header).chain(&rows) {
+            // The function name is left-aligned, the numbers right-aligned
+            let mut line = format!("  {:<width$}", row[0], width = widths[0]);
+            for (cell, width) in row.iter().zip(&widths).skip(1) {
+                let _ = write!(line, "  {cell:>width$}");
+            }
+            self.emit(&line);
+        }
+    }
+
+    /// Print the argument tuples evaluated more than once, most wasted calls first
+    pub fn flush_overlaps(&mut self) {
+        /// Maximum number of argument tuples listed
+        const SHOWN: usize = 10;
+
+        let style = self.style();
+        let overlaps = std::mem::take(&mut self.overlaps);
+        let mut repeated: Vec<_> = overlaps
+            .into_iter()
+            .filter(|(_, overlap)| overlap.count > 1)
+            .collect();
+        if repeated.is_empty() {
+            return;
+        }
+        repeated.sort_by_key(|(_, overlap)| Reverse(overlap.wasted_calls));
+        // Subtrees of repeated calls nest, so their wasted calls are not summed
+        let redundant_calls: usize = repeated.iter().map(|(_, overlap)| overlap.count - 1).sum();
+        self.emit(&format!(
+            "overlapping subproblems: {} argument tuples evaluated more than once, {} redundant calls",
+            repeated.len(),
+            redundant_calls
+        ));
+        for ((name, args), overlap) in repeated.iter().take(SHOWN) {
+            self.emit(&format!(
+                "  {name}({args}) {}{}, {} calls wasted",
+                style.times(),
+                overlap.count,
+                overlap.wasted_calls
+            ));
+        }
+        if repeated.len() > SHOWN {
+            self.emit(&format!(
+                "  {} {} more",
+                style.ellipsis(),
+                repeated.len() - SHOWN
+            ));
+        }
+    }
+
+    /// Print the header of the innermost call, holding it back if the call is compact
+    pub fn print_header(&mut self, depth: usize, header: &str) {
+        let compact = self.frames.last().is_some_and(|frame| frame.compact);
+        if compact && !header.contains('\n') {
+            self.flush_children();
+            self.flush_hidden_calls();
+            if let Some(frame) = self.frames.last_mut() {
+                frame.pending_header = Some((depth, header.to_string()));
+            }
+            return;
+        }
+        for line in header.lines() {
+            self.print_line(depth, line);
+        }
+        if header.is_empty() || header.ends_with('\n') {
+            self.print_line(depth, "");
+        }
+    }
+
+    /// Print the held back header of the innermost call, if any
+    pub fn flush_pending_header(&mut self) {
+        let Some(frame) = self.frames.last_mut() else {
+            return;
+        };
+        if let Some((depth, header)) = frame.pending_header.take() {
+            self.emit_indented(depth, format_args!("{header}"));
+        }
+    }
+
+    /// Print the return line of the innermost call, after the glyph of the drawing style
+    ///
+    /// The return line of a compact leaf call is merged with its held back header.
+    pub fn print_exit(&mut self, depth: usize, text: &str) {
+        let pending_header = self
+            .frames
+            .last_mut()
+            .and_then(|frame| frame.pending_header.take());
+        let mut lines = text.trim_start().lines();
+        let first = lines.next().unwrap_or_default();
+        let style = self.style();
+        self.flush_children();
+        self.flush_hidden_calls();
+        match (pending_header, first.strip_prefix("return: ")) {
+            (Some((_, header)), _) if first.is_empty() => {
+                self.emit_indented(depth, format_args!("{header}"));
+            }
+            (Some((_, header)), Some(value)) => {
+                let arrow = style.arrow();
+                self.emit_indented(depth, format_args!("{header} {arrow} {value}"));
+            }
+            (Some((_, header)), None) => {
+                let arrow = style.arrow();
+                self.emit_indented(depth, format_args!("{header} {arrow} {first}"));
+            }
+            (None, _) if first.is_empty() => {
+                let glyph = color::paint_glyph(depth, style.end());
+                self.emit_indented(depth, format_args!("{glyph}"));
+            }
+            (None, _) => {
+                let glyph = color::paint_glyph(depth, style.exit());
+                self.emit_indented(depth, format_args!("{glyph} {first}"));
+            }
+        }
+        for line in lines {
+            self.print_line(depth, line);
+        }
+        if text.ends_with('\n') {
+            self.print_line(depth, "");
+        }
+    }
+
+    /// Print a line of trace output, indented to `depth`
+    pub fn print_line(&mut self, depth: usize, line: &str) {
+        self.flush_pending_header();
+        self.flush_children();
+        self.flush_hidden_calls();
+        self.emit_indented(depth, format_args!("{line}"));
+    }
+
+    /// Drawing style of the innermost call
+    fn style(&self) -> TreeStyle {
+        self.frames
+            .last()
+            .map_or_else(config::tree_style, |frame| frame.style)
+    }
+
+    /// Write a line indented to `depth` in the drawing style of the innermost call
+    fn emit_indented(&mut self, depth: usize, text: fmt::Arguments) {
+        let max_guides = self
+            .frames
+            .last()
+            .map_or_else(config::max_guides, |frame| frame.max_guides);
+        self.emit_with_guides(self.style(), depth, max_guides, text);
+    }
+
+    /// Write a line indented to `depth`, rendered into the reused line buffer
+    fn emit_with_guides(
+        &mut self,
+        style: TreeStyle,
+        depth: usize,
+        max_guides: Option<usize>,
+        text: fmt::Arguments,
+    ) {
+        let mut line = std::mem::take(&mut self.line);
+        line.clear();
+        color::push_guides(&mut line, style, depth, max_guides);
+        let _ = line.write_fmt(text);
+        self.emit(&line);
+        self.line = line;
+    }
+
+    /// Count a call hidden by a limit, summarized at the depth of the first one
+    pub fn hide_call(&mut self, display_depth: usize) {
+        if self.hidden_calls == 0 {
+            self.hidden_depth = display_depth;
+        }
+        self.hidden_calls += 1;
+    }
+
+    /// Print the summary line of calls hidden by a depth limit, if any
+    pub fn flush_hidden_calls(&mut self) {
+        let depth = self.hidden_depth;
+        let ellipsis = self.style().ellipsis();
+        match self.hidden_calls {
+            0 => {}
+            1 => self.emit_indented(depth, format_args!("{ellipsis} 1 call hidden")),
+            n => self.emit_indented(depth, format_args!("{ellipsis} {n} calls hidden")),
+        }
+        self.hidden_calls = 0;
+    }
+
+    /// Print the held back output of the children of the innermost call
+    pub fn flush_children(&mut self) {
+        let Some(frame) = self.frames.last_mut() else {
+            return;
+        };
+        let elided_children = std::mem::take(&mut frame.elided_children);
+        let tail = std::mem::take(&mut frame.tail);
+        let (style, depth, max_guides) = (frame.style, frame.children_depth, frame.max_guides);
+        match elided_children {
+            0 => {}
+            1 => self.emit_with_guides(
+                style,
+                depth,
+                max_guides,
+                format_args!("{} 1 more call", style.ellipsis()),
+            ),
+            n => self.emit_with_guides(
+                style,
+                depth,
+                max_guides,
+                format_args!("{} {n} more calls", style.ellipsis()),
+            ),
+        }
+        for line in tail.into_iter().flatten() {
+            self.emit(&line);
+        }
+    }
+
+    /// Stop capturing the output of the finished child of the call at `index`, if it is captured
+    ///
+    /// The oldest captured child beyond the trailing ones shown is dropped.
+    pub fn end_capture(&mut self, index: usize) {
+        let parent = &mut self.frames[index];
+        if !std::mem::take(&mut parent.capturing) {
+            return;
+        }
+        self.capturing_frames.pop();
+        let last = parent.children_limit.map_or(0, |(_, last)| last);
+        if parent.tail.len() > last {
+            parent.tail.pop_front();
+            parent.elided_children += 1;
+        }
+    }
+
+    /// Stop counting the current child of the call at `index` as shown, e.g. when it is hidden
+    /// before printing anything
+    pub fn uncount_child(&mut self, index: usize) {
+        let parent = &mut self.frames[index];
+        if !parent.visible || parent.children_limit.is_none() {
+            return;
+        }
+        parent.shown_children -= 1;
+        if std::mem::take(&mut parent.capturing) {
+            self.capturing_frames.pop();
+            parent.tail.pop_back();
+        }
+    }
+
+    /// Write a rendered line to the output of the innermost capturing call, or to stderr
+    fn emit(&mut self, line: &str) {
+        match self.capturing_frames.last() {
+            Some(&index) => {
+                if let Some(buffer) = self.frames[index].tail.back_mut() {
+                    buffer.push(line.to_string());
+                }
+            }
+            None => self.write(line),
+        }
+    }
+
+    /// Write a rendered line to stderr, unless the output limit has been reached
+    ///
+    /// The line is flushed right away unless output is buffered.
+    fn write(&mut self, line: &str) {
+        if self.truncated {
+            return;
+        }
+        let bytes = line.len() + 1;
+        let over_lines = config::output_limit().is_some_and(|max| self.lines_written >= max);
+        let over_bytes =
+            config::output_byte_limit().is_some_and(|max| self.bytes_written + bytes > max);
+        let out = self
+            .out
+            .get_or_insert_with(|| BufWriter::with_capacity(1 << 16, io::stderr()));
+        if over_lines || over_bytes {
+            self.truncated = true;
+            let _ = writeln!(
+                out,
+                "[procon-lg: output truncated after {} lines]",
+                self.lines_written
+            );
+            let _ = out.flush();
+            return;
+        }
+        self.lines_written += 1;
+        self.bytes_written += bytes;
+        let _ = writeln!(out, "{line}");
+        if !config::buffered_output() {
+            let _ = out.flush();
+        }
+    }
+
+    /// Print a warning of the tracer after the trace output so far
+    ///
+    /// Warnings do not count towards the output limit.
+    pub fn warn(&mut self, message: &str) {
+        self.flush_output();
+        eprintln!("[procon-lg: warning: {message}]");
+    }
+
+    /// Write out the buffered trace output
+    pub fn flush_output(&mut self) {
+        if let Some(out) = &mut self.out {
+            let _ = out.flush();
+        }
+    }
+
+    /// Take a buffer for the rendered arguments of a call, reusing those of a finished call
+    pub fn take_args_buffer(&mut self) -> String {
+        self.spare_args.pop().unwrap_or_default()
+    }
+
+    /// Keep the buffer of the rendered arguments of a finished call for later calls
+    pub fn recycle_args(&mut self, args: Option<String>) {
+        if let Some(mut args) = args {
+            args.clear();
+            self.spare_args.push(args);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timed_frame(name: &'static str) -> Frame {
+        Frame {
+            name,
+            show_time: true,
+            started: Some(Instant::now()),
+            ..Frame::default()
+        }
+    }
+
+    #[test]
+    fn test_record_time_reuses_exit_measurement() {
+        LG_STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            state.frames.push(timed_frame("parent"));
+            state.frames.push(timed_frame("child"));
+
+            let times = state.frames[1].stop_clock();
+            std::thread::sleep(Duration::from_millis(2));
+            assert_eq!(state.frames[1].stop_clock(), times);
+
+            state.record_time();
+            let (time, self_time) = times.unwrap();
+            let recorded = &state.slowest_calls[0];
+            assert_eq!((recorded.time, recorded.self_time), (time, self_time));
+            assert_eq!(state.frames[0].children_time, time);
+        });
+    }
+}
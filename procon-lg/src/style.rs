@@ -0,0 +1,108 @@
+//! Drawing styles of the call tree
+
+use std::fmt::{self, Write};
+use std::time::Duration;
+
+/// Drawing style of the call tree
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TreeStyle {
+    /// `│ ` guides, with return lines like `└ return: 3` and `╵`
+    #[default]
+    Unicode,
+    /// `| ` guides, with return lines like `` `- return: 3 `` and `` `- ``, and only ASCII
+    /// elsewhere, e.g. `...` and `->`, for terminals and judges that mangle UTF-8
+    Ascii,
+    /// A depth prefix like `[3] ` instead of guides, with the ASCII return lines
+    Depth,
+}
+
+impl TreeStyle {
+    /// Guide drawn for each level of indentation, empty for the depth style which draws a prefix
+    pub(crate) fn guide(self) -> &'static str {
+        match self {
+            TreeStyle::Unicode => "│ ",
+            TreeStyle::Ascii => "| ",
+            TreeStyle::Depth => "",
+        }
+    }
+
+    /// Glyph starting a return line with text
+    pub(crate) fn exit(self) -> &'static str {
+        match self {
+            TreeStyle::Unicode => "└",
+            TreeStyle::Ascii | TreeStyle::Depth => "`-",
+        }
+    }
+
+    /// Return line of a call without text to show
+    pub(crate) fn end(self) -> &'static str {
+        match self {
+            TreeStyle::Unicode => "╵",
+            TreeStyle::Ascii | TreeStyle::Depth => "`-",
+        }
+    }
+
+    /// Marker of omitted calls or guides
+    pub(crate) fn ellipsis(self) -> &'static str {
+        match self {
+            TreeStyle::Unicode => "…",
+            TreeStyle::Ascii | TreeStyle::Depth => "...",
+        }
+    }
+
+    /// Arrow to a later value, e.g. the return value of a compact call
+    pub(crate) fn arrow(self) -> &'static str {
+        match self {
+            TreeStyle::Unicode => "→",
+            TreeStyle::Ascii | TreeStyle::Depth => "->",
+        }
+    }
+
+    /// Arrow from the id of a call to the id of its parent
+    pub(crate) fn back_arrow(self) -> &'static str {
+        match self {
+            TreeStyle::Unicode => "←",
+            TreeStyle::Ascii | TreeStyle::Depth => "<-",
+        }
+    }
+
+    /// Sign of a repetition count, e.g. `×3`
+    pub(crate) fn times(self) -> &'static str {
+        match self {
+            TreeStyle::Unicode => "×",
+            TreeStyle::Ascii | TreeStyle::Depth => "x",
+        }
+    }
+
+    /// Display a duration like `{:.2?}`, with `us` for microseconds outside the unicode style
+    pub(crate) fn duration(self, duration: Duration) -> StyledDuration {
+        StyledDuration(self, duration)
+    }
+}
+
+/// Duration displayed in a tree style, see [`TreeStyle::duration`]
+pub(crate) struct StyledDuration(TreeStyle, Duration);
+
+impl fmt::Display for StyledDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            TreeStyle::Unicode => write!(f, "{:.2?}", self.1),
+            TreeStyle::Ascii | TreeStyle::Depth => write!(AsciiMicros(f), "{:.2?}", self.1),
+        }
+    }
+}
+
+/// Writer replacing `µ` with `u`
+struct AsciiMicros<'a, W>(&'a mut W);
+
+impl<W: Write> Write for AsciiMicros<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for (i, part) in s.split('µ').enumerate() {
+            if i > 0 {
+                self.0.write_char('u')?;
+            }
+            self.0.write_str(part)?;
+        }
+        Ok(())
+    }
+}